@@ -0,0 +1,560 @@
+//! Absences registered by the school.
+
+use crate::{
+    agenda::{LessonId, SchoolYear},
+    csv::CsvFormat,
+    error::{EntityKind, Error, Result},
+    http::{TryJson, TrySend},
+    serde::Json,
+    upload::{self, File},
+    user::UserId,
+    Client,
+};
+use chrono::{
+    format::{Item, StrftimeItems},
+    Datelike, NaiveDate, NaiveTime,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    io::Write,
+};
+
+/// Fetches the absences matching a filter and writes them to a CSV file, one
+/// record per absence, preceded by a header record.
+///
+/// # Errors
+///
+/// Returns an error if the export's settings are invalid, the absences can't
+/// be fetched or the writer fails.
+pub async fn export_csv<W: Write>(
+    client: &Client<'_>,
+    filter: &AbsencesFilter,
+    export: &AbsenceExport,
+    mut writer: W,
+) -> Result<()> {
+    export.validate()?;
+    let absences = get_absences(client, filter.school_year).await?;
+
+    let header: Vec<&str> = export
+        .columns
+        .iter()
+        .map(|column| column.header())
+        .collect();
+    export.format.write_record(&mut writer, &header)?;
+    for absence in absences.iter().filter(|absence| filter.matches(absence)) {
+        let record: Vec<String> = export
+            .columns
+            .iter()
+            .map(|column| match column {
+                AbsenceColumn::Code => absence.code.to_string(),
+                AbsenceColumn::Date => absence.date.format(&export.date_format).to_string(),
+                AbsenceColumn::Hour => absence.hour.to_string(),
+                AbsenceColumn::Justified => {
+                    if absence.is_justified { "yes" } else { "no" }.to_owned()
+                }
+                AbsenceColumn::Kind => match absence.kind {
+                    AbsenceKind::Absence => "absence",
+                    AbsenceKind::Dispensation { .. } => "dispensation",
+                    AbsenceKind::Late { .. } => "late",
+                    AbsenceKind::Other => "other",
+                }
+                .to_owned(),
+                AbsenceColumn::Note => match &absence.kind {
+                    AbsenceKind::Dispensation { note } | AbsenceKind::Late { note, .. } => {
+                        note.clone()
+                    }
+                    _ => String::new(),
+                },
+                AbsenceColumn::Reason => absence.reason.clone(),
+            })
+            .collect();
+        export.format.write_record(&mut writer, &record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Returns the absences, late arrivals and dispensations of the logged-in
+/// student during a school year, sorted by date and hour in ascending order.
+///
+/// Parent accounts get the absences of the child selected with
+/// [`Client::select_child`](crate::Client::select_child).
+///
+/// # Errors
+///
+/// Returns an error if the school year is out of the range of dates that can
+/// be represented.
+pub async fn get_absences(client: &Client<'_>, school_year: SchoolYear) -> Result<Vec<Absence>> {
+    let (from, to) = match (school_year.first_day(), school_year.last_day()) {
+        (Some(from), Some(to)) => (from, to),
+        _ => {
+            return Err(Error::Validation {
+                field: "school_year",
+                reason: "must be within the range of representable dates".to_owned(),
+            })
+        }
+    };
+
+    let url = format!("{}/absences/api/v1/absences", client.url());
+    let mut absences: Vec<Absence> = client
+        .http_client()
+        .get(&url)
+        .query(&[("from", from.to_string()), ("to", to.to_string())])
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    absences.sort_by_key(|absence| (absence.date, absence.hour));
+    Ok(absences)
+}
+
+/// Files a justification for an absence, optionally along with a document like
+/// a doctor's note.
+///
+/// Only some platforms allow parents to justify absences.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The reason is empty.
+/// * The attachment has an [illegal file name](crate::upload::upload_file).
+/// * The absence doesn't exist or the logged-in user isn't allowed to justify
+///   it.
+pub async fn justify(
+    client: &Client<'_>,
+    absence_id: AbsenceId,
+    reason: &str,
+    attachment: Option<File>,
+) -> Result<()> {
+    if reason.trim().is_empty() {
+        return Err(Error::Validation {
+            field: "reason",
+            reason: "must not be empty".to_owned(),
+        });
+    }
+
+    let upload_dir = match attachment {
+        Some(file) => {
+            let upload_dir = upload::get_upload_directory(client).await?;
+            upload::upload_file(client, upload_dir.clone(), file).await?;
+            Some(upload_dir)
+        }
+        None => None,
+    };
+
+    let mut form = HashMap::new();
+    form.insert("reason", Json::Str(reason));
+    if let Some(upload_dir) = &upload_dir {
+        form.insert("uploadDir", Json::Str(upload_dir.as_str()));
+    }
+
+    let url = format!(
+        "{}/absences/api/v1/absences/{}/justification",
+        client.url(),
+        absence_id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Absence, absence_id))?;
+    Ok(())
+}
+
+/// Registers the attendance of students during a lesson, replacing any
+/// attendance that was registered before.
+///
+/// The attendance is registered in a single request, so either all or none of
+/// the entries are registered.
+///
+/// # Errors
+///
+/// Returns an error if the lesson doesn't exist or the logged-in user isn't
+/// allowed to register attendance for it.
+pub async fn register<'a, I>(client: &Client<'_>, lesson_id: LessonId, entries: I) -> Result<()>
+where
+    I: IntoIterator<Item = (&'a UserId, Attendance)>,
+{
+    let entries: Vec<_> = entries
+        .into_iter()
+        .map(|(student, attendance)| AttendanceEntry {
+            attendance,
+            student,
+        })
+        .collect();
+
+    let url = format!(
+        "{}/absences/api/v1/lessons/{}/attendance",
+        client.url(),
+        lesson_id
+    );
+    client
+        .http_client()
+        .put(&url)
+        .json(&entries)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Lesson, lesson_id))?;
+    Ok(())
+}
+
+/// An absence during a lesson hour, or a late arrival or dispensation
+/// registered for one.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Absence {
+    /// The code the absence was registered with.
+    pub code: AbsenceCode,
+    /// The date of the absence.
+    pub date: NaiveDate,
+    /// The index of the lesson hour, starting at `1`.
+    pub hour: u8,
+    /// The absence's identifier.
+    pub id: AbsenceId,
+    /// Whether the absence has been justified, e.g. by a doctor's note.
+    pub is_justified: bool,
+    /// The kind of the record, including its details.
+    #[serde(default, rename = "details")]
+    pub kind: AbsenceKind,
+    /// The reason for the absence, or an empty string if none was given.
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// A code an [`Absence`](crate::absences::Absence) is registered with, like
+/// `Z` for illness. Schools can define their own codes, so their meaning
+/// varies.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct AbsenceCode(String);
+
+impl AbsenceCode {
+    /// Returns a slice of the underlying string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for AbsenceCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<&str> for AbsenceCode {
+    fn from(s: &str) -> Self {
+        Self(s.to_owned())
+    }
+}
+
+impl From<String> for AbsenceCode {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+/// A column of an [absence export](crate::absences::export_csv).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum AbsenceColumn {
+    /// The code the absence was registered with.
+    Code,
+    /// The date of the absence, formatted using the export's date format.
+    Date,
+    /// The index of the lesson hour.
+    Hour,
+    /// `yes` if the absence has been justified, `no` otherwise.
+    Justified,
+    /// The kind of the record: `absence`, `late`, `dispensation` or `other`.
+    Kind,
+    /// The note of a late arrival or dispensation.
+    Note,
+    /// The reason for the absence.
+    Reason,
+}
+
+impl AbsenceColumn {
+    fn header(&self) -> &'static str {
+        match self {
+            AbsenceColumn::Code => "Code",
+            AbsenceColumn::Date => "Date",
+            AbsenceColumn::Hour => "Hour",
+            AbsenceColumn::Justified => "Justified",
+            AbsenceColumn::Kind => "Kind",
+            AbsenceColumn::Note => "Note",
+            AbsenceColumn::Reason => "Reason",
+        }
+    }
+}
+
+/// The settings of an [absence export](crate::absences::export_csv).
+///
+/// The default export contains every column, formats dates like `2024-09-01`
+/// and uses the default [`CsvFormat`](crate::csv::CsvFormat).
+#[derive(Clone, Debug)]
+pub struct AbsenceExport {
+    columns: Vec<AbsenceColumn>,
+    date_format: String,
+    format: CsvFormat,
+}
+
+impl AbsenceExport {
+    /// Creates an export with the default settings.
+    pub fn new() -> AbsenceExport {
+        AbsenceExport::default()
+    }
+
+    /// Sets the columns to export, in order.
+    pub fn columns<I: IntoIterator<Item = AbsenceColumn>>(mut self, columns: I) -> AbsenceExport {
+        self.columns = columns.into_iter().collect();
+        self
+    }
+
+    /// Sets the format of dates, using the syntax of
+    /// [`chrono::format::strftime`](chrono::format::strftime), e.g. `%d/%m/%Y`.
+    pub fn date_format<T: Into<String>>(mut self, date_format: T) -> AbsenceExport {
+        self.date_format = date_format.into();
+        self
+    }
+
+    /// Sets the CSV format.
+    pub fn format(mut self, format: CsvFormat) -> AbsenceExport {
+        self.format = format;
+        self
+    }
+
+    /// Checks that the export produces a well-formed file.
+    fn validate(&self) -> Result<()> {
+        self.format.validate()?;
+        if self.columns.is_empty() {
+            return Err(Error::Validation {
+                field: "columns",
+                reason: "must contain at least one column".to_owned(),
+            });
+        }
+        if StrftimeItems::new(&self.date_format).any(|item| item == Item::Error) {
+            return Err(Error::Validation {
+                field: "date_format",
+                reason: "must be a valid strftime format".to_owned(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for AbsenceExport {
+    fn default() -> Self {
+        Self {
+            columns: vec![
+                AbsenceColumn::Date,
+                AbsenceColumn::Hour,
+                AbsenceColumn::Kind,
+                AbsenceColumn::Code,
+                AbsenceColumn::Justified,
+                AbsenceColumn::Reason,
+                AbsenceColumn::Note,
+            ],
+            date_format: "%Y-%m-%d".to_owned(),
+            format: CsvFormat::default(),
+        }
+    }
+}
+
+/// A handle to an [`Absence`](crate::absences::Absence).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct AbsenceId(u64);
+
+impl AbsenceId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for AbsenceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for AbsenceId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The kind of an [`Absence`](crate::absences::Absence) record.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum AbsenceKind {
+    /// The student was absent during the lesson hour.
+    #[default]
+    Absence,
+    /// The student was exempted from the lesson, e.g. from physical education
+    /// for medical reasons.
+    #[serde(rename_all = "camelCase")]
+    Dispensation {
+        /// The note explaining the dispensation, or an empty string if none
+        /// was added.
+        #[serde(default)]
+        note: String,
+    },
+    /// The student arrived late.
+    #[serde(rename_all = "camelCase")]
+    Late {
+        /// The time when the student arrived, if it was registered.
+        arrived_at: Option<NaiveTime>,
+        /// The note explaining the late arrival, or an empty string if none
+        /// was added.
+        #[serde(default)]
+        note: String,
+    },
+    /// A kind of record that isn't known to this crate.
+    #[serde(other)]
+    Other,
+}
+
+/// Aggregates of a set of absences, where every lesson hour counts as one
+/// absence.
+///
+/// Only records of kind [`Absence`](crate::absences::AbsenceKind::Absence) are
+/// counted, except by [`late`](crate::absences::AbsenceSummary::late).
+#[derive(Clone, Debug)]
+pub struct AbsenceSummary {
+    absences: Vec<Absence>,
+    late: usize,
+}
+
+impl AbsenceSummary {
+    /// Creates a summary of the specified absences.
+    pub fn new<I: IntoIterator<Item = Absence>>(absences: I) -> Self {
+        let mut late = 0;
+        let absences = absences
+            .into_iter()
+            .filter(|absence| match absence.kind {
+                AbsenceKind::Absence => true,
+                AbsenceKind::Late { .. } => {
+                    late += 1;
+                    false
+                }
+                _ => false,
+            })
+            .collect();
+        Self { absences, late }
+    }
+
+    /// Returns the number of absences per code.
+    pub fn by_code(&self) -> BTreeMap<AbsenceCode, usize> {
+        let mut counts = BTreeMap::new();
+        for absence in &self.absences {
+            *counts.entry(absence.code.clone()).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Returns the number of absences per trimester. The first trimester runs
+    /// from September until December, the second from January until March and
+    /// the third from April until August.
+    pub fn by_trimester(&self) -> [usize; 3] {
+        let mut counts = [0; 3];
+        for absence in &self.absences {
+            let trimester = match absence.date.month() {
+                9..=12 => 0,
+                1..=3 => 1,
+                _ => 2,
+            };
+            counts[trimester] += 1;
+        }
+        counts
+    }
+
+    /// Returns the number of late arrivals.
+    pub fn late(&self) -> usize {
+        self.late
+    }
+
+    /// Returns the total number of absences.
+    pub fn total(&self) -> usize {
+        self.absences.len()
+    }
+
+    /// Returns the number of absences that haven't been justified.
+    pub fn unjustified(&self) -> usize {
+        self.absences
+            .iter()
+            .filter(|absence| !absence.is_justified)
+            .count()
+    }
+}
+
+/// A filter used to [`export_csv`](crate::absences::export_csv) absences.
+///
+/// Filters are combined, so an absence has to match all of them to be
+/// exported.
+#[derive(Clone, Debug)]
+pub struct AbsencesFilter {
+    school_year: SchoolYear,
+    since: Option<NaiveDate>,
+    unjustified_only: bool,
+    until: Option<NaiveDate>,
+}
+
+impl AbsencesFilter {
+    /// Creates a filter matching every absence during a school year.
+    pub fn new(school_year: SchoolYear) -> AbsencesFilter {
+        AbsencesFilter {
+            school_year,
+            since: None,
+            unjustified_only: false,
+            until: None,
+        }
+    }
+
+    /// Only matches absences on or after the specified date.
+    pub fn since(mut self, date: NaiveDate) -> AbsencesFilter {
+        self.since = Some(date);
+        self
+    }
+
+    /// Only matches absences that haven't been justified.
+    pub fn unjustified_only(mut self) -> AbsencesFilter {
+        self.unjustified_only = true;
+        self
+    }
+
+    /// Only matches absences on or before the specified date.
+    pub fn until(mut self, date: NaiveDate) -> AbsencesFilter {
+        self.until = Some(date);
+        self
+    }
+
+    fn matches(&self, absence: &Absence) -> bool {
+        self.since.is_none_or(|since| absence.date >= since)
+            && self.until.is_none_or(|until| absence.date <= until)
+            && !(self.unjustified_only && absence.is_justified)
+    }
+}
+
+/// The attendance of a student during a lesson, as registered by
+/// [`register`](crate::absences::register).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "status", content = "code", rename_all = "camelCase")]
+pub enum Attendance {
+    /// The student is absent, registered with the specified code.
+    Absent(AbsenceCode),
+    /// The student arrived late.
+    Late,
+    /// The student is present.
+    Present,
+}
+
+#[derive(Serialize)]
+struct AttendanceEntry<'a> {
+    attendance: Attendance,
+    student: &'a UserId,
+}
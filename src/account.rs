@@ -0,0 +1,546 @@
+//! The logged-in user's account and profile.
+
+use crate::{
+    error::{EntityKind, Error, Result},
+    http::{self, TryJson, TrySend},
+    serde::Json,
+    upload::{self, File},
+    user::{ClassId, UserId},
+    Client,
+};
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset};
+use futures::{Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Reverse, collections::HashMap, fmt, net::IpAddr};
+
+/// Returns the recent logins and other security-related activity on the
+/// logged-in user's account, sorted by date in descending order.
+pub async fn get_activity_log(client: &Client<'_>) -> Result<Vec<Activity>> {
+    let url = format!("{}/account/api/v1/activity", client.url());
+    let mut activities: Vec<Activity> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    activities.sort_by_key(|activity| Reverse(activity.date));
+    Ok(activities)
+}
+
+/// Returns the co-accounts of the logged-in user, sorted by number.
+///
+/// Co-accounts are additional logins for the same user, typically handed out
+/// to parents.
+pub async fn get_coaccounts(client: &Client<'_>) -> Result<Vec<CoAccount>> {
+    let url = format!("{}/account/api/v1/coaccounts", client.url());
+    let mut coaccounts: Vec<CoAccount> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    coaccounts.sort_by_key(|coaccount| coaccount.number);
+    Ok(coaccounts)
+}
+
+/// Returns the language of the logged-in user's interface.
+pub async fn get_language(client: &Client<'_>) -> Result<Language> {
+    let url = format!("{}/account/api/v1/settings/language", client.url());
+    let setting: LanguageSetting = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    Ok(setting.language)
+}
+
+/// Returns the accounts linked to the logged-in user: the children of a parent
+/// account, or the parents of a student where the platform shows them.
+///
+/// The identifiers of children can be passed to
+/// [`Client::select_child`](crate::Client::select_child) to view their data.
+pub async fn get_linked_accounts(client: &Client<'_>) -> Result<Vec<LinkedAccount>> {
+    let url = format!("{}/account/api/v1/linked-accounts", client.url());
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await
+}
+
+/// Returns the logged-in user's notification preferences for each module.
+pub async fn get_notification_settings(client: &Client<'_>) -> Result<Vec<NotificationSetting>> {
+    let url = format!("{}/account/api/v1/notification-settings", client.url());
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await
+}
+
+/// Downloads a user's profile picture, scaled to the specified width and height
+/// in pixels, and returns its contents as a non-blocking stream of
+/// [`Bytes`](bytes::Bytes).
+///
+/// Any user's picture can be downloaded, given its hash.
+///
+/// # Errors
+///
+/// Returns an error if the picture doesn't exist.
+pub async fn get_picture(
+    client: &Client<'_>,
+    hash: &PictureHash,
+    size: u32,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let url = format!(
+        "{}/account/api/v1/pictures/{}",
+        client.url(),
+        http::encode_segment(hash.as_str())
+    );
+    let response = client
+        .http_client()
+        .get(&url)
+        .query(&[("size", size)])
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Picture, hash))?;
+    Ok(response.bytes_stream().err_into())
+}
+
+/// Returns the profile of the logged-in user.
+pub async fn get_profile(client: &Client<'_>) -> Result<Profile> {
+    let url = format!("{}/account/api/v1/profile", client.url());
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await
+}
+
+/// Activates or deactivates one of the logged-in user's co-accounts.
+/// Deactivated co-accounts can't log in.
+///
+/// Only main accounts are allowed to manage co-accounts, and some platforms
+/// don't allow it at all.
+///
+/// # Errors
+///
+/// Returns an error if the co-account doesn't exist or the logged-in user
+/// isn't allowed to manage it.
+pub async fn set_coaccount_active(client: &Client<'_>, number: u8, active: bool) -> Result<()> {
+    let mut form = HashMap::new();
+    form.insert("isActive", Json::Bool(active));
+
+    let url = format!("{}/account/api/v1/coaccounts/{}", client.url(), number);
+    client
+        .http_client()
+        .patch(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::CoAccount, number))?;
+    Ok(())
+}
+
+/// Sets the password of one of the logged-in user's co-accounts, e.g. to hand
+/// it out to a parent.
+///
+/// Only main accounts are allowed to manage co-accounts, and some platforms
+/// don't allow it at all.
+///
+/// # Errors
+///
+/// Returns an error if the password is empty, the co-account doesn't exist or
+/// the logged-in user isn't allowed to manage it.
+pub async fn set_coaccount_password(client: &Client<'_>, number: u8, password: &str) -> Result<()> {
+    if password.is_empty() {
+        return Err(Error::Validation {
+            field: "password",
+            reason: "must not be empty".to_owned(),
+        });
+    }
+
+    let mut form = HashMap::new();
+    form.insert("password", Json::Str(password));
+
+    let url = format!(
+        "{}/account/api/v1/coaccounts/{}/password",
+        client.url(),
+        number
+    );
+    client
+        .http_client()
+        .put(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::CoAccount, number))?;
+    Ok(())
+}
+
+/// Changes the language of the logged-in user's interface, including the
+/// language of messages returned by the API.
+///
+/// # Errors
+///
+/// Returns an error if the platform doesn't support the language.
+pub async fn set_language(client: &Client<'_>, language: Language) -> Result<()> {
+    let setting = LanguageSetting { language };
+    let url = format!("{}/account/api/v1/settings/language", client.url());
+    client
+        .http_client()
+        .put(&url)
+        .json(&setting)
+        .try_send()
+        .await?;
+    Ok(())
+}
+
+/// Changes the logged-in user's notification preferences. Modules that aren't
+/// included are left unchanged.
+///
+/// # Errors
+///
+/// Returns an error if one of the modules doesn't exist or doesn't send
+/// notifications.
+pub async fn set_notification_settings(
+    client: &Client<'_>,
+    settings: &[NotificationSetting],
+) -> Result<()> {
+    let url = format!("{}/account/api/v1/notification-settings", client.url());
+    client
+        .http_client()
+        .put(&url)
+        .json(settings)
+        .try_send()
+        .await?;
+    Ok(())
+}
+
+/// Replaces the logged-in user's profile picture with an image.
+///
+/// # Errors
+///
+/// Returns an error if the file has an [illegal file
+/// name](crate::upload::upload_file) or the server doesn't accept the image.
+pub async fn set_picture(client: &Client<'_>, image: File) -> Result<()> {
+    let upload_dir = upload::get_upload_directory(client).await?;
+    upload::upload_file(client, upload_dir.clone(), image).await?;
+
+    let mut form = HashMap::new();
+    form.insert("uploadDir", Json::Str(upload_dir.as_str()));
+
+    let url = format!("{}/account/api/v1/profile/picture", client.url());
+    client
+        .http_client()
+        .put(&url)
+        .json(&form)
+        .try_send()
+        .await?;
+    Ok(())
+}
+
+/// Changes the editable fields of the logged-in user's profile. Fields that
+/// aren't set in the update are left unchanged.
+///
+/// # Errors
+///
+/// Returns an [`Error::Api`](crate::Error::Api) with code
+/// [`InvalidEmail`](crate::error::KnownErrorCode::InvalidEmail) or
+/// [`InvalidPhone`](crate::error::KnownErrorCode::InvalidPhone) if the server
+/// rejects the shown email address or phone number.
+pub async fn update_profile(client: &Client<'_>, update: ProfileUpdate) -> Result<()> {
+    let url = format!("{}/account/api/v1/profile", client.url());
+    client
+        .http_client()
+        .patch(&url)
+        .json(&update)
+        .try_send()
+        .await?;
+    Ok(())
+}
+
+/// An entry in an account's activity log.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Activity {
+    /// The date of the activity.
+    pub date: DateTime<FixedOffset>,
+    /// A description of the device, usually its browser's user agent string.
+    #[serde(default)]
+    pub device: String,
+    /// The IP address the activity originated from.
+    pub ip: IpAddr,
+    /// The kind of the activity.
+    #[serde(rename = "type")]
+    pub kind: ActivityKind,
+}
+
+/// The kind of an [`Activity`](crate::account::Activity).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActivityKind {
+    /// A login attempt that failed, e.g. because of a wrong password.
+    FailedLogin,
+    /// A successful login.
+    Login,
+    /// A logout.
+    Logout,
+    /// A change of the account's password.
+    PasswordChange,
+    /// A kind of activity that isn't known to this crate.
+    #[serde(other)]
+    Other,
+}
+
+/// A co-account of a user.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoAccount {
+    /// Whether the co-account can log in.
+    pub is_active: bool,
+    /// The name of the person using the co-account, like a parent's name, or
+    /// an empty string if none is filled in.
+    #[serde(default)]
+    pub name: String,
+    /// The co-account's number, starting at `1`.
+    pub number: u8,
+}
+
+/// A language of the interface.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum Language {
+    /// Dutch, with code `nl`.
+    Dutch,
+    /// English, with code `en`.
+    English,
+    /// French, with code `fr`.
+    French,
+    /// German, with code `de`.
+    German,
+    /// A language that isn't known to this crate, containing its code.
+    Other(String),
+}
+
+impl Language {
+    /// Returns the language's code as it is used by the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Language::Dutch => "nl",
+            Language::English => "en",
+            Language::French => "fr",
+            Language::German => "de",
+            Language::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<String> for Language {
+    fn from(code: String) -> Self {
+        match code.to_lowercase().as_str() {
+            "nl" | "nl_be" => Language::Dutch,
+            "en" | "en_gb" => Language::English,
+            "fr" | "fr_be" => Language::French,
+            "de" | "de_de" => Language::German,
+            _ => Language::Other(code),
+        }
+    }
+}
+
+impl From<Language> for String {
+    fn from(language: Language) -> Self {
+        match language {
+            Language::Other(code) => code,
+            language => language.as_str().to_owned(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct LanguageSetting {
+    language: Language,
+}
+
+/// An account linked to the logged-in user.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkedAccount {
+    /// The linked user's class, or `None` if they aren't a student.
+    pub class: Option<ClassId>,
+    /// The linked user's identifier.
+    pub id: UserId,
+    /// The linked user's full name.
+    pub name: String,
+    /// How the linked user relates to the logged-in user.
+    pub relationship: Relationship,
+}
+
+/// The channels through which a user is notified of a module's events.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationSetting {
+    /// Whether push notifications are sent to the mobile app.
+    pub app: bool,
+    /// Whether notifications are sent by email.
+    pub email: bool,
+    /// The name of the module, e.g. `Messages` or `Skore`.
+    pub module: String,
+    /// Whether notifications are shown on the website.
+    pub web: bool,
+}
+
+/// A handle to a user's profile picture, which can be downloaded with
+/// [`get_picture`](crate::account::get_picture).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct PictureHash(String);
+
+impl PictureHash {
+    /// Returns a slice of the underlying string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PictureHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<&str> for PictureHash {
+    fn from(s: &str) -> Self {
+        Self(s.to_owned())
+    }
+}
+
+impl From<String> for PictureHash {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+/// A user's profile.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    /// The user's description of themselves, like their hobbies, or an empty
+    /// string if they haven't written one.
+    #[serde(default)]
+    pub about: String,
+    /// The user's class, or `None` if the user isn't a student.
+    pub class: Option<ClassId>,
+    /// The official email address assigned to the user by the school.
+    pub email: String,
+    /// The user's first name.
+    pub first_name: String,
+    /// The user's identifier.
+    pub id: UserId,
+    /// The user's internal number, assigned by the school's administration,
+    /// or `None` if none is assigned.
+    pub internal_number: Option<String>,
+    /// The user's last name.
+    pub last_name: String,
+    /// The user's phone number, or `None` if they haven't entered one.
+    pub phone: Option<String>,
+    /// The hash of the user's profile picture.
+    pub picture: PictureHash,
+    /// The user's roles on the platform.
+    #[serde(default)]
+    pub roles: Vec<Role>,
+    /// The email address the user shows to other users, or `None` if they
+    /// don't show one.
+    pub shown_email: Option<String>,
+    /// The name the user logs in with.
+    pub username: String,
+}
+
+/// Changes to the editable fields of a profile, as applied by
+/// [`update_profile`](crate::account::update_profile).
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    about: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shown_email: Option<String>,
+}
+
+impl ProfileUpdate {
+    /// Creates an update that doesn't change anything.
+    pub fn new() -> ProfileUpdate {
+        ProfileUpdate::default()
+    }
+
+    /// Changes the user's description of themselves. An empty string removes
+    /// the description.
+    pub fn about<T: Into<String>>(mut self, about: T) -> ProfileUpdate {
+        self.about = Some(about.into());
+        self
+    }
+
+    /// Changes the user's phone number. An empty string removes the phone
+    /// number.
+    pub fn phone<T: Into<String>>(mut self, phone: T) -> ProfileUpdate {
+        self.phone = Some(phone.into());
+        self
+    }
+
+    /// Changes the email address shown to other users. An empty string hides
+    /// the email address.
+    pub fn shown_email<T: Into<String>>(mut self, email: T) -> ProfileUpdate {
+        self.shown_email = Some(email.into());
+        self
+    }
+}
+
+/// How a [`LinkedAccount`](crate::account::LinkedAccount) relates to the
+/// logged-in user.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Relationship {
+    /// The linked user is a child of the logged-in user.
+    Child,
+    /// The linked user is a parent or guardian of the logged-in user.
+    Parent,
+    /// A relationship that isn't known to this crate.
+    #[serde(other)]
+    Other,
+}
+
+/// A role a user can have on the platform.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Role {
+    /// An administrator of the platform.
+    Administrator,
+    /// A parent or guardian of a student.
+    Parent,
+    /// A student.
+    Student,
+    /// A teacher.
+    Teacher,
+    /// A role that isn't known to this crate.
+    #[serde(other)]
+    Other,
+}
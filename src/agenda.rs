@@ -0,0 +1,866 @@
+//! The agenda containing the lessons of the logged-in user.
+
+use crate::{
+    error::{EntityKind, Error, Result},
+    http::{encode_segment, TryJson, TrySend},
+    poll,
+    serde::Json,
+    user::{ClassId, UserId},
+    Client,
+};
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveTime, Utc, Weekday};
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, io::Write, time::Duration};
+
+/// Attaches a test or homework assignment to a lesson. The task's course is
+/// ignored, as the task always belongs to the lesson's course.
+///
+/// Only the lesson's teacher is allowed to add assignments.
+///
+/// # Errors
+///
+/// Returns an error if the task's description is empty, the lesson doesn't
+/// exist or the logged-in user isn't allowed to edit it.
+pub async fn add_assignment(client: &Client<'_>, id: LessonId, task: &AgendaTask) -> Result<()> {
+    if task.description.trim().is_empty() {
+        return Err(Error::Validation {
+            field: "description",
+            reason: "must not be empty".to_owned(),
+        });
+    }
+
+    let assignment = NewAssignment {
+        description: &task.description,
+        due: task.due,
+        kind: &task.kind,
+        material: &task.material,
+    };
+    let url = format!("{}/agenda/api/v1/lessons/{}/assignments", client.url(), id);
+    client
+        .http_client()
+        .post(&url)
+        .json(&assignment)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Lesson, id))?;
+    Ok(())
+}
+
+/// Fetches the lessons between two dates (both inclusive) and writes them to
+/// an iCalendar file. See [`to_ics`](crate::agenda::to_ics) for details.
+///
+/// # Errors
+///
+/// Returns an error if the lessons can't be fetched or the writer fails.
+pub async fn export_ics<W: Write>(
+    client: &Client<'_>,
+    from: NaiveDate,
+    to: NaiveDate,
+    mut writer: W,
+) -> Result<()> {
+    let lessons = get_lessons(client, from, to).await?;
+    writer.write_all(to_ics(&lessons).as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Returns the lessons of a class between two dates (both inclusive), sorted
+/// by start date in ascending order.
+///
+/// # Errors
+///
+/// Returns an error if the class doesn't exist or the logged-in user isn't
+/// allowed to view its timetable.
+pub async fn get_class_schedule(
+    client: &Client<'_>,
+    class_id: &ClassId,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Lesson>> {
+    let url = format!(
+        "{}/agenda/api/v1/classes/{}/lessons",
+        client.url(),
+        encode_segment(class_id.as_str())
+    );
+    fetch_lessons(client, &url, from, to)
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Class, class_id))
+}
+
+/// Returns the holidays and other days without lessons in a school year,
+/// sorted by date in ascending order. Holidays spanning several days are
+/// returned as one entry per day.
+pub async fn get_free_days(client: &Client<'_>, school_year: SchoolYear) -> Result<Vec<FreeDay>> {
+    let url = format!("{}/agenda/api/v1/free-days", client.url());
+    let periods: Vec<FreePeriod> = client
+        .http_client()
+        .get(&url)
+        .query(&[("schoolYear", school_year.start_year())])
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+
+    let mut days = Vec::new();
+    for period in periods {
+        let mut date = period.from;
+        while date <= period.to {
+            days.push(FreeDay {
+                date,
+                name: period.name.clone(),
+            });
+            date = match date.succ_opt() {
+                Some(date) => date,
+                None => break,
+            };
+        }
+    }
+    days.sort_by_key(|day| day.date);
+    Ok(days)
+}
+
+/// Returns the school's period grid, sorted by hour in ascending order.
+///
+/// The grid can be used to convert the hour indexes of lessons to clock times
+/// using [`Lesson::clock_times`](crate::agenda::Lesson::clock_times).
+pub async fn get_hour_definitions(client: &Client<'_>) -> Result<Vec<HourDefinition>> {
+    let url = format!("{}/agenda/api/v1/hours", client.url());
+    let mut hours: Vec<HourDefinition> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    hours.sort_by_key(|hour| hour.hour);
+    Ok(hours)
+}
+
+/// Returns the lessons of the logged-in user between two dates (both
+/// inclusive), sorted by start date in ascending order.
+pub async fn get_lessons(
+    client: &Client<'_>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Lesson>> {
+    let url = format!("{}/agenda/api/v1/lessons", client.url());
+    fetch_lessons(client, &url, from, to).await
+}
+
+/// Returns the tests and homework attached to the lessons between two dates
+/// (both inclusive), sorted by due date in ascending order.
+pub async fn get_tasks(
+    client: &Client<'_>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<AgendaTask>> {
+    let mut tasks: Vec<AgendaTask> = get_lessons(client, from, to)
+        .await?
+        .into_iter()
+        .flat_map(|lesson| lesson.tasks)
+        .collect();
+    tasks.sort_by_key(|task| task.due);
+    Ok(tasks)
+}
+
+/// Returns the lessons taught by a teacher between two dates (both inclusive),
+/// sorted by start date in ascending order.
+///
+/// # Errors
+///
+/// Returns an error if the user doesn't exist or the logged-in user isn't
+/// allowed to view their timetable.
+pub async fn get_teacher_schedule(
+    client: &Client<'_>,
+    teacher_id: &UserId,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Lesson>> {
+    let url = format!(
+        "{}/agenda/api/v1/teachers/{}/lessons",
+        client.url(),
+        encode_segment(teacher_id.as_str())
+    );
+    fetch_lessons(client, &url, from, to)
+        .await
+        .map_err(|err| err.for_entity(EntityKind::User, teacher_id))
+}
+
+/// Returns the tests of the logged-in user from today up to the specified
+/// horizon (inclusive), across all courses, sorted by due date in ascending
+/// order.
+///
+/// Today is determined using the local time zone.
+///
+/// # Errors
+///
+/// Returns an error if the end of the horizon is out of the range of dates that
+/// can be represented.
+pub async fn get_upcoming_tests(
+    client: &Client<'_>,
+    horizon: chrono::Duration,
+) -> Result<Vec<AgendaTask>> {
+    let today = Local::now().date_naive();
+    let last_day = today
+        .checked_add_signed(horizon)
+        .ok_or_else(|| Error::Validation {
+            field: "horizon",
+            reason: "must be within the range of representable dates".to_owned(),
+        })?;
+    let mut tests = get_tasks(client, today, last_day).await?;
+    tests.retain(|task| task.kind == TaskKind::Test && task.due >= today);
+    Ok(tests)
+}
+
+/// Changes the personal note on a lesson. An empty note removes the note.
+///
+/// Personal notes are only visible to the logged-in user.
+///
+/// # Errors
+///
+/// Returns an error if the lesson doesn't exist.
+pub async fn set_note(client: &Client<'_>, id: LessonId, note: &str) -> Result<()> {
+    let mut form = HashMap::new();
+    form.insert("note", Json::Str(note));
+
+    let url = format!("{}/agenda/api/v1/lessons/{}/note", client.url(), id);
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Lesson, id))?;
+    Ok(())
+}
+
+/// Converts lessons to an iCalendar file, which can be imported in or
+/// subscribed to from most calendar applications.
+///
+/// Every lesson becomes an event with the course as its title, the room as its
+/// location and the teacher, subject and tasks as its description. Times are
+/// converted to UTC, so the file displays correctly in every time zone.
+pub fn to_ics(lessons: &[Lesson]) -> String {
+    const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+    let mut ics = String::new();
+    let mut line = |line: String| fold_ics_line(&mut ics, &line);
+    line("BEGIN:VCALENDAR".to_owned());
+    line("VERSION:2.0".to_owned());
+    line("PRODID:-//rust-smartschool//agenda//EN".to_owned());
+    line("CALSCALE:GREGORIAN".to_owned());
+
+    let now = Utc::now().format(DATE_FORMAT);
+    for lesson in lessons {
+        let mut description = format!("{}\n{}", lesson.teacher, lesson.subject);
+        for task in &lesson.tasks {
+            description.push_str(&format!("\n{}: {}", task.kind, task.description));
+        }
+
+        line("BEGIN:VEVENT".to_owned());
+        line(format!("UID:lesson-{}@smartschool", lesson.id));
+        line(format!("DTSTAMP:{}", now));
+        line(format!(
+            "DTSTART:{}",
+            lesson.start.with_timezone(&Utc).format(DATE_FORMAT)
+        ));
+        line(format!(
+            "DTEND:{}",
+            lesson.end.with_timezone(&Utc).format(DATE_FORMAT)
+        ));
+        line(format!("SUMMARY:{}", escape_ics_text(&lesson.course)));
+        line(format!("LOCATION:{}", escape_ics_text(&lesson.room)));
+        line(format!(
+            "DESCRIPTION:{}",
+            escape_ics_text(description.trim_end())
+        ));
+        if lesson.is_cancelled {
+            line("STATUS:CANCELLED".to_owned());
+        }
+        line("END:VEVENT".to_owned());
+    }
+
+    line("END:VCALENDAR".to_owned());
+    ics
+}
+
+/// Changes the subject or content of a lesson, as shown to its students.
+///
+/// Only the lesson's teacher is allowed to change its content.
+///
+/// # Errors
+///
+/// Returns an error if the lesson doesn't exist or the logged-in user isn't
+/// allowed to edit it.
+pub async fn update_lesson_content(client: &Client<'_>, id: LessonId, subject: &str) -> Result<()> {
+    let mut form = HashMap::new();
+    form.insert("subject", Json::Str(subject));
+
+    let url = format!("{}/agenda/api/v1/lessons/{}/content", client.url(), id);
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Lesson, id))?;
+    Ok(())
+}
+
+/// Returns a non-blocking stream of changes to the lessons between two dates
+/// (both inclusive), polling the server at the specified interval.
+///
+/// The lessons fetched when the stream is first polled serve as the baseline
+/// and aren't yielded. Every subsequent fetch is compared to the previous one
+/// and the differences are yielded in the order of the lessons' start dates.
+/// Errors are yielded as they occur, after which polling continues at the same
+/// interval, so the stream never ends by itself.
+pub fn watch<'a>(
+    client: &'a Client<'_>,
+    from: NaiveDate,
+    to: NaiveDate,
+    interval: Duration,
+) -> BoxStream<'a, Result<ScheduleChange>> {
+    poll::every(
+        interval,
+        None,
+        move |previous: Option<HashMap<LessonId, Lesson>>| async move {
+            match get_lessons(client, from, to).await {
+                Ok(lessons) => {
+                    let current = lessons
+                        .into_iter()
+                        .map(|lesson| (lesson.id, lesson))
+                        .collect();
+                    let changes = match &previous {
+                        Some(previous) => diff_lessons(previous, &current),
+                        None => Vec::new(),
+                    };
+                    (Some(current), Ok(changes))
+                }
+                Err(err) => (previous, Err(err)),
+            }
+        },
+    )
+}
+
+/// Returns the length of a lesson whose length isn't specified, in hours.
+fn default_length() -> u8 {
+    1
+}
+
+/// Compares two fetches of the same date range and returns the changes,
+/// sorted by the start date of the changed lesson.
+///
+/// A lesson that disappears from the agenda is reported as cancelled.
+fn diff_lessons(
+    previous: &HashMap<LessonId, Lesson>,
+    current: &HashMap<LessonId, Lesson>,
+) -> Vec<ScheduleChange> {
+    let mut changes = Vec::new();
+    for lesson in current.values() {
+        let before = match previous.get(&lesson.id) {
+            Some(before) => before,
+            None => {
+                if !lesson.is_cancelled {
+                    changes.push(ScheduleChange::Added(lesson.clone()));
+                }
+                continue;
+            }
+        };
+        if lesson.is_cancelled {
+            if !before.is_cancelled {
+                changes.push(ScheduleChange::Cancelled(lesson.clone()));
+            }
+            continue;
+        }
+        if before.is_cancelled {
+            changes.push(ScheduleChange::Added(lesson.clone()));
+            continue;
+        }
+        if lesson.start != before.start || lesson.end != before.end {
+            changes.push(ScheduleChange::Moved {
+                before: before.clone(),
+                after: lesson.clone(),
+            });
+        }
+        if lesson.teacher != before.teacher {
+            changes.push(ScheduleChange::TeacherReplaced {
+                before: before.clone(),
+                after: lesson.clone(),
+            });
+        }
+    }
+    for before in previous.values() {
+        if !before.is_cancelled && !current.contains_key(&before.id) {
+            changes.push(ScheduleChange::Cancelled(before.clone()));
+        }
+    }
+    changes.sort_by_key(|change| change.lesson().start);
+    changes
+}
+
+/// Escapes the characters that have a special meaning in iCalendar text
+/// values.
+fn escape_ics_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fetches the lessons between two dates from a lesson listing endpoint and
+/// fills in the course of the tasks attached to them.
+async fn fetch_lessons(
+    client: &Client<'_>,
+    url: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Lesson>> {
+    let mut lessons: Vec<Lesson> = client
+        .http_client()
+        .get(url)
+        .query(&[("from", from.to_string()), ("to", to.to_string())])
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    for lesson in &mut lessons {
+        for task in &mut lesson.tasks {
+            if task.course.is_empty() {
+                task.course = lesson.course.clone();
+            }
+        }
+    }
+    Ok(lessons)
+}
+
+/// Appends an iCalendar content line, folded into lines of at most 75 bytes as
+/// required by RFC 5545.
+fn fold_ics_line(ics: &mut String, line: &str) {
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            // The leading space counts towards the line length.
+            length = 1;
+        }
+        ics.push(c);
+        length += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+/// A test or homework assignment attached to a lesson.
+#[derive(Clone, Debug, Deserialize, Hash, Serialize)]
+pub struct AgendaTask {
+    /// The name of the course of the lesson the task is attached to.
+    #[serde(default)]
+    pub course: String,
+    /// The task's description.
+    pub description: String,
+    /// The date when the task is due, usually the date of the lesson it is
+    /// attached to.
+    pub due: NaiveDate,
+    /// The kind of the task.
+    #[serde(rename = "type")]
+    pub kind: TaskKind,
+    /// The material to study for a test, or an empty string if the teacher
+    /// didn't specify any.
+    #[serde(default)]
+    pub material: String,
+}
+
+/// A day without lessons.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct FreeDay {
+    /// The date of the day.
+    pub date: NaiveDate,
+    /// The name of the holiday or the reason why there are no lessons.
+    pub name: String,
+}
+
+/// A range of days without lessons, as returned by the server.
+#[derive(Deserialize)]
+struct FreePeriod {
+    from: NaiveDate,
+    name: String,
+    to: NaiveDate,
+}
+
+/// An hour in the school's period grid.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct HourDefinition {
+    /// The time when the hour ends.
+    pub end: NaiveTime,
+    /// The hour's index, starting at `1`.
+    pub hour: u8,
+    /// The hour's display name, e.g. `1ste lesuur`.
+    pub name: String,
+    /// The time when the hour starts.
+    pub start: NaiveTime,
+}
+
+/// A lesson in the agenda.
+#[derive(Clone, Debug, Deserialize, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lesson {
+    /// The name of the lesson's course.
+    pub course: String,
+    /// The date when the lesson ends.
+    pub end: DateTime<FixedOffset>,
+    /// The index of the lesson's first hour in the school's period grid,
+    /// starting at `1`.
+    pub hour: u8,
+    /// The lesson's identifier.
+    pub id: LessonId,
+    /// `true` if the lesson has been cancelled.
+    #[serde(default)]
+    pub is_cancelled: bool,
+    /// The number of consecutive hours the lesson takes up, usually `1`.
+    #[serde(default = "default_length")]
+    pub length: u8,
+    /// The logged-in user's personal note on the lesson, or an empty string if
+    /// there is none.
+    #[serde(default)]
+    pub note: String,
+    /// The room where the lesson takes place.
+    pub room: String,
+    /// The date when the lesson starts.
+    pub start: DateTime<FixedOffset>,
+    /// The lesson's subject or content, as filled in by the teacher.
+    #[serde(default)]
+    pub subject: String,
+    /// The tests and homework attached to the lesson.
+    #[serde(default, rename = "assignments")]
+    pub tasks: Vec<AgendaTask>,
+    /// The name of the lesson's teacher.
+    pub teacher: String,
+}
+
+impl Lesson {
+    /// Returns the times when the lesson starts and ends according to the
+    /// school's period grid, as returned by
+    /// [`get_hour_definitions`](crate::agenda::get_hour_definitions).
+    ///
+    /// Returns `None` if the grid doesn't contain the lesson's first or last
+    /// hour.
+    pub fn clock_times(&self, hours: &[HourDefinition]) -> Option<(NaiveTime, NaiveTime)> {
+        let last = self.hour.checked_add(self.length.max(1) - 1)?;
+        let find = |index| hours.iter().find(|hour| hour.hour == index);
+        Some((find(self.hour)?.start, find(last)?.end))
+    }
+}
+
+/// A handle to a [`Lesson`](crate::agenda::Lesson).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct LessonId(u64);
+
+impl LessonId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for LessonId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for LessonId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The request body to attach an assignment to a lesson.
+#[derive(Serialize)]
+struct NewAssignment<'a> {
+    description: &'a str,
+    due: NaiveDate,
+    #[serde(rename = "type")]
+    kind: &'a TaskKind,
+    material: &'a str,
+}
+
+/// A change to the agenda, as yielded by
+/// [`watch`](crate::agenda::watch).
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum ScheduleChange {
+    /// A lesson has been added or a cancelled lesson has been restored.
+    Added(Lesson),
+    /// A lesson has been cancelled or removed from the agenda.
+    Cancelled(Lesson),
+    /// A lesson has been moved to another time.
+    Moved {
+        /// The lesson before the change.
+        before: Lesson,
+        /// The lesson after the change.
+        after: Lesson,
+    },
+    /// A lesson is taught by another teacher.
+    TeacherReplaced {
+        /// The lesson before the change.
+        before: Lesson,
+        /// The lesson after the change.
+        after: Lesson,
+    },
+}
+
+impl ScheduleChange {
+    /// Returns the changed lesson. For moves and teacher replacements, this is
+    /// the lesson after the change.
+    pub fn lesson(&self) -> &Lesson {
+        match self {
+            ScheduleChange::Added(lesson) | ScheduleChange::Cancelled(lesson) => lesson,
+            ScheduleChange::Moved { after, .. } | ScheduleChange::TeacherReplaced { after, .. } => {
+                after
+            }
+        }
+    }
+}
+
+/// A lesson occupying an hour in a [`WeekSchedule`](crate::agenda::WeekSchedule).
+#[derive(Clone, Copy, Debug)]
+pub struct ScheduleSlot<'a> {
+    /// The lesson occupying the hour.
+    pub lesson: &'a Lesson,
+    /// The position of the hour within the lesson, `0` for its first hour and
+    /// `1` for the second hour of a double hour.
+    pub offset: u8,
+}
+
+/// A school year, running from the 1st of September until the 31st of August
+/// of the next year.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct SchoolYear(i32);
+
+impl SchoolYear {
+    /// Returns the school year starting in September of the specified year.
+    pub fn new(start_year: i32) -> Self {
+        Self(start_year)
+    }
+
+    /// Returns the school year containing today's date, determined using the
+    /// local time zone.
+    pub fn current() -> Self {
+        Self::containing(Local::now().date_naive())
+    }
+
+    /// Returns the school year containing the specified date.
+    pub fn containing(date: NaiveDate) -> Self {
+        if date.month() >= 9 {
+            Self(date.year())
+        } else {
+            Self(date.year() - 1)
+        }
+    }
+
+    /// Returns the first day of the school year, or `None` if it is out of the
+    /// range of dates that can be represented.
+    pub fn first_day(&self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.0, 9, 1)
+    }
+
+    /// Returns the last day of the school year, or `None` if it is out of the
+    /// range of dates that can be represented.
+    pub fn last_day(&self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.0.checked_add(1)?, 8, 31)
+    }
+
+    /// Returns the year in which the school year starts.
+    pub fn start_year(&self) -> i32 {
+        self.0
+    }
+}
+
+impl fmt::Display for SchoolYear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.0, i64::from(self.0) + 1)
+    }
+}
+
+/// The kind of an [`AgendaTask`](crate::agenda::AgendaTask).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum TaskKind {
+    /// Homework, labeled `taak` or `huiswerk` by the platform.
+    Homework,
+    /// A test, labeled `toets` or `test` by the platform.
+    Test,
+    /// A kind of task that isn't known to this crate, containing the
+    /// platform's label.
+    Other(String),
+}
+
+impl fmt::Display for TaskKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskKind::Homework => write!(f, "Homework"),
+            TaskKind::Test => write!(f, "Test"),
+            TaskKind::Other(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+impl From<String> for TaskKind {
+    fn from(label: String) -> Self {
+        match label.to_lowercase().as_str() {
+            "homework" | "huiswerk" | "taak" => TaskKind::Homework,
+            "test" | "toets" => TaskKind::Test,
+            _ => TaskKind::Other(label),
+        }
+    }
+}
+
+impl From<TaskKind> for String {
+    fn from(kind: TaskKind) -> Self {
+        match kind {
+            TaskKind::Homework => "taak".to_owned(),
+            TaskKind::Test => "toets".to_owned(),
+            TaskKind::Other(label) => label,
+        }
+    }
+}
+
+/// Lessons arranged into a grid of weekdays and hours, ready to be rendered as
+/// a timetable.
+///
+/// Lessons spanning several hours occupy every one of their hours, and lessons
+/// taking place in parallel, such as those of different groups of a class,
+/// share the same hours.
+///
+/// # Example
+///
+/// ```ignore
+/// let lessons = agenda::get_lessons(&client, monday, friday).await?;
+/// let schedule = WeekSchedule::new(monday, lessons);
+/// for hour in 1..=schedule.hours() {
+///     for day in schedule.days() {
+///         for slot in schedule.slots(day, hour) {
+///             // ...
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct WeekSchedule {
+    hours: u8,
+    lessons: Vec<Lesson>,
+    slots: HashMap<(Weekday, u8), Vec<(usize, u8)>>,
+    week_start: NaiveDate,
+}
+
+impl WeekSchedule {
+    /// Arranges lessons into the grid of the week containing the specified
+    /// date. Lessons outside of that week are ignored.
+    ///
+    /// A week that is only partially within the range of dates that can be
+    /// represented is cut off at the first or last representable date.
+    pub fn new<I: IntoIterator<Item = Lesson>>(date: NaiveDate, lessons: I) -> Self {
+        let week_start = date
+            .checked_sub_signed(chrono::Duration::days(
+                date.weekday().num_days_from_monday().into(),
+            ))
+            .unwrap_or(NaiveDate::MIN);
+        let week_end = week_start
+            .checked_add_signed(chrono::Duration::days(6))
+            .unwrap_or(NaiveDate::MAX);
+
+        let mut lessons: Vec<Lesson> = lessons
+            .into_iter()
+            .filter(|lesson| {
+                let date = lesson.start.date_naive();
+                date >= week_start && date <= week_end
+            })
+            .collect();
+        lessons.sort_by(|a, b| (a.start, &a.course).cmp(&(b.start, &b.course)));
+
+        let mut hours = 0;
+        let mut slots: HashMap<_, Vec<_>> = HashMap::new();
+        for (index, lesson) in lessons.iter().enumerate() {
+            let day = lesson.start.weekday();
+            for offset in 0..lesson.length.max(1) {
+                let hour = match lesson.hour.checked_add(offset) {
+                    Some(hour) => hour,
+                    None => break,
+                };
+                hours = hours.max(hour);
+                slots.entry((day, hour)).or_default().push((index, offset));
+            }
+        }
+
+        Self {
+            hours,
+            lessons,
+            slots,
+            week_start,
+        }
+    }
+
+    /// Returns the days to display, i.e. Monday through Friday, followed by
+    /// Saturday and Sunday if lessons take place on them.
+    pub fn days(&self) -> Vec<Weekday> {
+        let mut days = vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ];
+        for day in [Weekday::Sat, Weekday::Sun] {
+            if self
+                .lessons
+                .iter()
+                .any(|lesson| lesson.start.weekday() == day)
+            {
+                days.push(day);
+            }
+        }
+        days
+    }
+
+    /// Returns the highest hour occupied by a lesson, or `0` if the week
+    /// doesn't contain any lessons.
+    pub fn hours(&self) -> u8 {
+        self.hours
+    }
+
+    /// Returns the lessons in the week, sorted by start date in ascending
+    /// order.
+    pub fn lessons(&self) -> &[Lesson] {
+        &self.lessons
+    }
+
+    /// Returns the lessons occupying an hour on a day, sorted by start date and
+    /// course name. More than one lesson is returned if lessons take place in
+    /// parallel.
+    pub fn slots(&self, day: Weekday, hour: u8) -> Vec<ScheduleSlot<'_>> {
+        self.slots
+            .get(&(day, hour))
+            .into_iter()
+            .flatten()
+            .map(|&(index, offset)| ScheduleSlot {
+                lesson: &self.lessons[index],
+                offset,
+            })
+            .collect()
+    }
+
+    /// Returns the date of the Monday of the week, or the first date that can
+    /// be represented if the Monday can't be.
+    pub fn week_start(&self) -> NaiveDate {
+        self.week_start
+    }
+}
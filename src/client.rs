@@ -1,8 +1,10 @@
 //! A client for interacting with a Smartschool instance.
 
 use crate::{
-    error::{Error, Result},
-    http::TrySend,
+    error::{EntityKind, Error, Result},
+    http::{TryJson, TrySend},
+    serde::Json,
+    user::{Child, UserId},
 };
 use regex::Regex;
 use reqwest::{redirect, Client as HttpClient};
@@ -19,7 +21,7 @@ fn get_params(body: &str) -> Option<(&str, &str)> {
         .filter_map(|captures| captures.get(1))
         .map(|capture| capture.as_str())
         .collect();
-    Some((params.get(0)?, params.get(1)?))
+    Some((params.first()?, params.get(1)?))
 }
 
 /// An asynchronous client for interacting with a Smartschool instance.
@@ -30,6 +32,20 @@ pub struct Client<'a> {
 }
 
 impl<'a> Client<'a> {
+    /// Returns the children linked to the logged-in parent account.
+    ///
+    /// Use [`select_child`](crate::Client::select_child) to view the data of
+    /// one of them.
+    pub async fn children(&self) -> Result<Vec<Child>> {
+        let url = format!("{}/parent/api/v1/children", self.url);
+        self.http_client
+            .get(&url)
+            .try_send()
+            .await?
+            .try_json()
+            .await
+    }
+
     /// Logs in with the provided login credentials and returns a client.
     ///
     /// The URL specifies the location of the Smartschool instance, usually a
@@ -66,7 +82,12 @@ impl<'a> Client<'a> {
             .post(&request_url)
             .form(&form)
             .try_send()
-            .await?;
+            .await
+            .map_err(|err| match err {
+                // A failed login attempt redirects back to the login page.
+                Error::SessionExpired => Error::Authentication,
+                err => err,
+            })?;
 
         let successful = response
             .cookies()
@@ -83,6 +104,30 @@ impl<'a> Client<'a> {
         &self.http_client
     }
 
+    /// Selects the child whose data is returned by subsequent requests, such
+    /// as the agenda, results and absences, for parent accounts with several
+    /// children.
+    ///
+    /// The selection is stored in the session, so it applies to every clone of
+    /// this client until another child is selected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the child isn't linked to the logged-in account.
+    pub async fn select_child(&self, child_id: &UserId) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("childId", Json::Str(child_id.as_str()));
+
+        let url = format!("{}/parent/api/v1/switch", self.url);
+        self.http_client
+            .post(&url)
+            .json(&form)
+            .try_send()
+            .await
+            .map_err(|err| err.for_entity(EntityKind::User, child_id))?;
+        Ok(())
+    }
+
     /// Returns the URL of the associated Smartschool instance.
     pub fn url(&self) -> &str {
         self.url
@@ -0,0 +1,1514 @@
+//! The courses the logged-in user is enrolled in or teaches.
+
+use crate::{
+    error::{EntityKind, Error, Result},
+    file::{self, UniquePaths},
+    http::{TryJson, TrySend},
+    mydoc::{self, FileId},
+    serde::Json,
+    upload::{self, File, UploadDirectory},
+    user::{ClassId, UserId},
+    Client,
+};
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset};
+use futures::{
+    stream::{self, Stream},
+    StreamExt, TryStreamExt,
+};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Adds a link to a course's weblinks area and returns it.
+///
+/// The link is restricted to the specified classes. An empty slice makes the
+/// link visible to every member of the course.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The title is empty or the URL is invalid.
+/// * The course doesn't exist.
+/// * The logged-in user isn't allowed to edit the course's weblinks.
+pub async fn add_weblink(
+    client: &Client<'_>,
+    course_id: CourseId,
+    title: &str,
+    url: &str,
+    description: &str,
+    visible_for: &[ClassId],
+) -> Result<Weblink> {
+    let weblink = WeblinkForm {
+        description,
+        title,
+        url,
+        visible_for,
+    };
+    weblink.validate()?;
+
+    let request_url = format!(
+        "{}/courses/api/v1/courses/{}/weblinks",
+        client.url(),
+        course_id
+    );
+    client
+        .http_client()
+        .post(&request_url)
+        .json(&weblink)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Course, course_id))?
+        .try_json()
+        .await
+}
+
+/// Creates a folder in a course's documents area and returns it.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The name is empty or contains illegal characters.
+/// * The course or parent folder doesn't exist.
+/// * The logged-in user isn't allowed to edit the course's documents.
+pub async fn create_folder(
+    client: &Client<'_>,
+    course_id: CourseId,
+    parent_id: Option<DocumentFolderId>,
+    name: &str,
+) -> Result<DocumentFolder> {
+    mydoc::validate_name("name", name)?;
+
+    let folder = NewDocumentFolder { name, parent_id };
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/documents/folders",
+        client.url(),
+        course_id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .json(&folder)
+        .try_send()
+        .await
+        .map_err(|err| match parent_id {
+            Some(parent_id) => err
+                .for_identified_entity(EntityKind::Folder, parent_id)
+                .for_identified_entity(EntityKind::Course, course_id),
+            None => err.for_entity(EntityKind::Course, course_id),
+        })?
+        .try_json()
+        .await
+}
+
+/// Returns a builder to download every document of a course's documents area
+/// into a local directory, mirroring the area's folder structure.
+///
+/// # Example
+///
+/// ```ignore
+/// let summary = courses::download_all(&client, course_id, "Wiskunde")
+///     .incremental()
+///     .execute()
+///     .await?;
+/// ```
+pub fn download_all<'a, 'b, P: AsRef<Path>>(
+    client: &'a Client<'b>,
+    course_id: CourseId,
+    dir: P,
+) -> DownloadAll<'a, 'b> {
+    DownloadAll {
+        client,
+        concurrency: 4,
+        course_id,
+        dir: dir.as_ref().to_owned(),
+        incremental: false,
+    }
+}
+
+/// Downloads a document from a course's documents area and returns its
+/// contents as a non-blocking stream of [`Bytes`](bytes::Bytes).
+///
+/// # Errors
+///
+/// Returns an error if the course or document doesn't exist.
+pub async fn download_document(
+    client: &Client<'_>,
+    course_id: CourseId,
+    id: DocumentId,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/documents/files/{}/download",
+        client.url(),
+        course_id,
+        id
+    );
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| {
+            err.for_identified_entity(EntityKind::File, id)
+                .for_identified_entity(EntityKind::Course, course_id)
+        })?;
+    Ok(response.bytes_stream().err_into())
+}
+
+/// Downloads an attachment of a course's news item and returns its contents as
+/// a non-blocking stream of [`Bytes`](bytes::Bytes).
+///
+/// # Errors
+///
+/// Returns an error if the news item doesn't exist.
+pub async fn download_news_attachment(
+    client: &Client<'_>,
+    course_id: CourseId,
+    item_id: NewsItemId,
+    attachment_id: NewsAttachmentId,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/news/{}/attachments/{}/download",
+        client.url(),
+        course_id,
+        item_id,
+        attachment_id
+    );
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| {
+            err.for_identified_entity(EntityKind::NewsItem, item_id)
+                .for_identified_entity(EntityKind::Course, course_id)
+        })?;
+    Ok(response.bytes_stream().err_into())
+}
+
+/// Downloads the files of a submission in an uploadzone as a zip archive and
+/// returns its contents as a non-blocking stream of [`Bytes`](bytes::Bytes).
+///
+/// Only the uploadzone's teachers are allowed to download submissions.
+///
+/// # Errors
+///
+/// Returns an error if the submission doesn't exist.
+pub async fn download_submission(
+    client: &Client<'_>,
+    uploadzone_id: UploadzoneId,
+    id: SubmissionId,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let url = format!(
+        "{}/courses/api/v1/uploadzones/{}/submissions/{}/download",
+        client.url(),
+        uploadzone_id,
+        id
+    );
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| {
+            err.for_identified_entity(EntityKind::Submission, id)
+                .for_identified_entity(EntityKind::Uploadzone, uploadzone_id)
+        })?;
+    Ok(response.bytes_stream().err_into())
+}
+
+/// Downloads the files of every submission in an uploadzone as a single zip
+/// archive, with a folder per student, and returns its contents as a
+/// non-blocking stream of [`Bytes`](bytes::Bytes).
+///
+/// Only the uploadzone's teachers are allowed to download submissions.
+///
+/// # Errors
+///
+/// Returns an error if the uploadzone doesn't exist.
+pub async fn download_submissions(
+    client: &Client<'_>,
+    uploadzone_id: UploadzoneId,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let url = format!(
+        "{}/courses/api/v1/uploadzones/{}/submissions/download",
+        client.url(),
+        uploadzone_id
+    );
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Uploadzone, uploadzone_id))?;
+    Ok(response.bytes_stream().err_into())
+}
+
+/// Saves the changes made to a link in a course's weblinks area and returns
+/// the updated link.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The title is empty or the URL is invalid.
+/// * The link doesn't exist.
+/// * The logged-in user isn't allowed to edit the course's weblinks.
+pub async fn edit_weblink(
+    client: &Client<'_>,
+    course_id: CourseId,
+    weblink: &Weblink,
+) -> Result<Weblink> {
+    let form = WeblinkForm {
+        description: &weblink.description,
+        title: &weblink.title,
+        url: &weblink.url,
+        visible_for: &weblink.visible_for,
+    };
+    form.validate()?;
+
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/weblinks/{}",
+        client.url(),
+        course_id,
+        weblink.id
+    );
+    client
+        .http_client()
+        .put(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| {
+            err.for_identified_entity(EntityKind::Weblink, weblink.id)
+                .for_identified_entity(EntityKind::Course, course_id)
+        })?
+        .try_json()
+        .await
+}
+
+/// Returns the courses the logged-in user is enrolled in or teaches, sorted by
+/// name in ascending order.
+pub async fn get_courses(client: &Client<'_>) -> Result<Vec<Course>> {
+    let url = format!("{}/courses/api/v1/courses", client.url());
+    let mut courses: Vec<Course> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    courses.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(courses)
+}
+
+/// Walks a course's documents area and returns every document in it, along
+/// with the folders containing it.
+///
+/// The result can be stored and compared to a later walk using
+/// [`DocumentTreeSnapshot::diff`](crate::courses::DocumentTreeSnapshot::diff)
+/// to detect new and updated documents.
+///
+/// # Errors
+///
+/// Returns an error if the course doesn't exist or a folder can't be listed.
+pub async fn get_document_tree(
+    client: &Client<'_>,
+    course_id: CourseId,
+) -> Result<DocumentTreeSnapshot> {
+    let mut entries = Vec::new();
+    let mut folders = vec![(None, Vec::new())];
+    while let Some((folder, path)) = folders.pop() {
+        let (documents, subfolders) = get_documents(client, course_id, folder).await?;
+        for subfolder in subfolders {
+            let mut subfolder_path = path.clone();
+            subfolder_path.push(subfolder.name);
+            folders.push((Some(subfolder.id), subfolder_path));
+        }
+        for document in documents {
+            entries.push(DocumentTreeEntry {
+                document,
+                folders: path.clone(),
+            });
+        }
+    }
+    entries.sort_by(|a, b| (&a.folders, &a.document.name).cmp(&(&b.folders, &b.document.name)));
+    Ok(DocumentTreeSnapshot { entries })
+}
+
+/// Returns the documents and subfolders of a folder in a course's documents
+/// area, or of the area's root folder if no folder is specified.
+///
+/// # Errors
+///
+/// Returns an error if the course or folder doesn't exist.
+pub async fn get_documents(
+    client: &Client<'_>,
+    course_id: CourseId,
+    folder: Option<DocumentFolderId>,
+) -> Result<(Vec<Document>, Vec<DocumentFolder>)> {
+    let url = match folder {
+        Some(folder) => format!(
+            "{}/courses/api/v1/courses/{}/documents/{}",
+            client.url(),
+            course_id,
+            folder
+        ),
+        None => format!(
+            "{}/courses/api/v1/courses/{}/documents",
+            client.url(),
+            course_id
+        ),
+    };
+    let response: GetDocuments = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| match folder {
+            Some(folder) => err.for_entity(EntityKind::Folder, folder),
+            None => err.for_entity(EntityKind::Course, course_id),
+        })?
+        .try_json()
+        .await?;
+    Ok((response.documents, response.folders))
+}
+
+/// Returns the attempts at a series of exercises, sorted by the date they were
+/// finished in ascending order.
+///
+/// Students only see their own attempts, whereas teachers see the attempts of
+/// every student.
+///
+/// # Errors
+///
+/// Returns an error if the series doesn't exist.
+pub async fn get_exercise_attempts(
+    client: &Client<'_>,
+    course_id: CourseId,
+    series_id: ExerciseSeriesId,
+) -> Result<Vec<ExerciseAttempt>> {
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/exercises/{}/attempts",
+        client.url(),
+        course_id,
+        series_id
+    );
+    let mut attempts: Vec<ExerciseAttempt> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| {
+            err.for_identified_entity(EntityKind::ExerciseSeries, series_id)
+                .for_identified_entity(EntityKind::Course, course_id)
+        })?
+        .try_json()
+        .await?;
+    attempts.sort_by_key(|attempt| attempt.finished_at);
+    Ok(attempts)
+}
+
+/// Returns the series of exercises available in a course.
+///
+/// # Errors
+///
+/// Returns an error if the course doesn't exist.
+pub async fn get_exercises(
+    client: &Client<'_>,
+    course_id: CourseId,
+) -> Result<Vec<ExerciseSeries>> {
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/exercises",
+        client.url(),
+        course_id
+    );
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Course, course_id))?
+        .try_json()
+        .await
+}
+
+/// Returns the students and teachers of a course, sorted by role and name.
+///
+/// # Errors
+///
+/// Returns an error if the course doesn't exist or the logged-in user isn't
+/// allowed to view its members.
+pub async fn get_members(client: &Client<'_>, course_id: CourseId) -> Result<Vec<CourseMember>> {
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/members",
+        client.url(),
+        course_id
+    );
+    let mut members: Vec<CourseMember> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Course, course_id))?
+        .try_json()
+        .await?;
+    members.sort_by(|a, b| (a.role, &a.name).cmp(&(b.role, &b.name)));
+    Ok(members)
+}
+
+/// Returns the news items and announcements posted in a course, sorted by date
+/// in descending order.
+///
+/// # Errors
+///
+/// Returns an error if the course doesn't exist.
+pub async fn get_news(client: &Client<'_>, course_id: CourseId) -> Result<Vec<NewsItem>> {
+    let url = format!("{}/courses/api/v1/courses/{}/news", client.url(), course_id);
+    let mut news: Vec<NewsItem> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Course, course_id))?
+        .try_json()
+        .await?;
+    news.sort_by_key(|item| Reverse(item.date));
+    Ok(news)
+}
+
+/// Returns the submissions received in an uploadzone, sorted by submission
+/// date in ascending order.
+///
+/// Only the uploadzone's teachers are allowed to view its submissions.
+///
+/// # Errors
+///
+/// Returns an error if the uploadzone doesn't exist.
+pub async fn get_submissions(
+    client: &Client<'_>,
+    uploadzone_id: UploadzoneId,
+) -> Result<Vec<Submission>> {
+    let url = format!(
+        "{}/courses/api/v1/uploadzones/{}/submissions",
+        client.url(),
+        uploadzone_id
+    );
+    let mut submissions: Vec<Submission> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Uploadzone, uploadzone_id))?
+        .try_json()
+        .await?;
+    submissions.sort_by_key(|submission| submission.submitted_at);
+    Ok(submissions)
+}
+
+/// Returns the uploadzones of a course, where students hand in assignments,
+/// including the logged-in user's submission status.
+///
+/// # Errors
+///
+/// Returns an error if the course doesn't exist.
+pub async fn get_uploadzones(client: &Client<'_>, course_id: CourseId) -> Result<Vec<Uploadzone>> {
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/uploadzones",
+        client.url(),
+        course_id
+    );
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Course, course_id))?
+        .try_json()
+        .await
+}
+
+/// Returns the links in a course's weblinks area.
+///
+/// # Errors
+///
+/// Returns an error if the course doesn't exist.
+pub async fn get_weblinks(client: &Client<'_>, course_id: CourseId) -> Result<Vec<Weblink>> {
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/weblinks",
+        client.url(),
+        course_id
+    );
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Course, course_id))?
+        .try_json()
+        .await
+}
+
+/// Records a grade and a comment for a submission, which are shown to the
+/// student. An empty string removes the grade or comment.
+///
+/// Only the uploadzone's teachers are allowed to grade submissions.
+///
+/// # Errors
+///
+/// Returns an error if the submission doesn't exist.
+pub async fn grade_submission(
+    client: &Client<'_>,
+    uploadzone_id: UploadzoneId,
+    id: SubmissionId,
+    grade: &str,
+    comment: &str,
+) -> Result<()> {
+    let mut form = HashMap::new();
+    form.insert("comment", Json::Str(comment));
+    form.insert("grade", Json::Str(grade));
+
+    let url = format!(
+        "{}/courses/api/v1/uploadzones/{}/submissions/{}/grade",
+        client.url(),
+        uploadzone_id,
+        id
+    );
+    client
+        .http_client()
+        .put(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| {
+            err.for_identified_entity(EntityKind::Submission, id)
+                .for_identified_entity(EntityKind::Uploadzone, uploadzone_id)
+        })?;
+    Ok(())
+}
+
+/// Copies a file from the logged-in user's personal documents to a folder in
+/// a course's documents area and returns the created document.
+///
+/// The copy is made on the server, so the file doesn't have to be downloaded
+/// and uploaded again.
+///
+/// # Errors
+///
+/// Returns an error if the course, folder or file doesn't exist, or the
+/// logged-in user isn't allowed to edit the course's documents.
+pub async fn import_from_mydoc(
+    client: &Client<'_>,
+    course_id: CourseId,
+    parent_id: Option<DocumentFolderId>,
+    file_id: FileId,
+) -> Result<Document> {
+    let import = ImportFromMydoc { file_id, parent_id };
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/documents/import",
+        client.url(),
+        course_id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .json(&import)
+        .try_send()
+        .await
+        .map_err(|err| {
+            let err = err
+                .for_identified_entity(EntityKind::File, file_id)
+                .for_identified_entity(EntityKind::Course, course_id);
+            match parent_id {
+                Some(parent_id) => err.for_identified_entity(EntityKind::Folder, parent_id),
+                None => err,
+            }
+        })?
+        .try_json()
+        .await
+}
+
+/// Removes a link from a course's weblinks area.
+///
+/// # Errors
+///
+/// Returns an error if the link doesn't exist or the logged-in user isn't
+/// allowed to edit the course's weblinks.
+pub async fn remove_weblink(client: &Client<'_>, course_id: CourseId, id: WeblinkId) -> Result<()> {
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/weblinks/{}",
+        client.url(),
+        course_id,
+        id
+    );
+    client
+        .http_client()
+        .delete(&url)
+        .try_send()
+        .await
+        .map_err(|err| {
+            err.for_identified_entity(EntityKind::Weblink, id)
+                .for_identified_entity(EntityKind::Course, course_id)
+        })?;
+    Ok(())
+}
+
+/// Restricts a document to the specified classes. An empty slice makes the
+/// document visible to every member of the course.
+///
+/// # Errors
+///
+/// Returns an error if the document doesn't exist or the logged-in user isn't
+/// allowed to edit the course's documents.
+pub async fn set_document_visibility(
+    client: &Client<'_>,
+    course_id: CourseId,
+    id: DocumentId,
+    classes: &[ClassId],
+) -> Result<()> {
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/documents/files/{}/visibility",
+        client.url(),
+        course_id,
+        id
+    );
+    client
+        .http_client()
+        .put(&url)
+        .json(&Visibility { classes })
+        .try_send()
+        .await
+        .map_err(|err| {
+            err.for_identified_entity(EntityKind::File, id)
+                .for_identified_entity(EntityKind::Course, course_id)
+        })?;
+    Ok(())
+}
+
+/// Restricts a folder and its contents to the specified classes. An empty
+/// slice makes the folder visible to every member of the course.
+///
+/// # Errors
+///
+/// Returns an error if the folder doesn't exist or the logged-in user isn't
+/// allowed to edit the course's documents.
+pub async fn set_folder_visibility(
+    client: &Client<'_>,
+    course_id: CourseId,
+    id: DocumentFolderId,
+    classes: &[ClassId],
+) -> Result<()> {
+    let url = format!(
+        "{}/courses/api/v1/courses/{}/documents/folders/{}/visibility",
+        client.url(),
+        course_id,
+        id
+    );
+    client
+        .http_client()
+        .put(&url)
+        .json(&Visibility { classes })
+        .try_send()
+        .await
+        .map_err(|err| {
+            err.for_identified_entity(EntityKind::Folder, id)
+                .for_identified_entity(EntityKind::Course, course_id)
+        })?;
+    Ok(())
+}
+
+/// Hands in files in an uploadzone.
+///
+/// Submitting again adds the files to the earlier submission.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * No files are specified.
+/// * One of the files has an [illegal file
+///   name](crate::upload::upload_file).
+/// * The uploadzone doesn't exist or no longer accepts submissions.
+pub async fn submit(
+    client: &Client<'_>,
+    uploadzone_id: UploadzoneId,
+    files: Vec<File>,
+) -> Result<()> {
+    if files.is_empty() {
+        return Err(Error::Validation {
+            field: "files",
+            reason: "must contain at least one file".to_owned(),
+        });
+    }
+
+    let upload_dir = upload::get_upload_directory(client).await?;
+    for file in files {
+        upload::upload_file(client, upload_dir.clone(), file).await?;
+    }
+
+    let mut form = HashMap::new();
+    form.insert("uploadDir", Json::Str(upload_dir.as_str()));
+
+    let url = format!(
+        "{}/courses/api/v1/uploadzones/{}/submissions",
+        client.url(),
+        uploadzone_id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Uploadzone, uploadzone_id))?;
+    Ok(())
+}
+
+/// Adds the files in an upload directory to a folder in a course's documents
+/// area and returns the created documents.
+///
+/// Use the [`upload`](crate::upload) module to fill the upload directory.
+///
+/// # Errors
+///
+/// Returns an error if the course or folder doesn't exist or the logged-in user
+/// isn't allowed to edit the course's documents.
+pub async fn upload_documents(
+    client: &Client<'_>,
+    course_id: CourseId,
+    parent_id: Option<DocumentFolderId>,
+    upload_dir: &UploadDirectory,
+) -> Result<Vec<Document>> {
+    let mut form = HashMap::new();
+    form.insert("uploadDir", Json::Str(upload_dir.as_str()));
+
+    let url = match parent_id {
+        Some(parent_id) => format!(
+            "{}/courses/api/v1/courses/{}/documents/{}/upload",
+            client.url(),
+            course_id,
+            parent_id
+        ),
+        None => format!(
+            "{}/courses/api/v1/courses/{}/documents/upload",
+            client.url(),
+            course_id
+        ),
+    };
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| match parent_id {
+            Some(parent_id) => err
+                .for_identified_entity(EntityKind::Folder, parent_id)
+                .for_identified_entity(EntityKind::Course, course_id),
+            None => err.for_entity(EntityKind::Course, course_id),
+        })?
+        .try_json()
+        .await
+}
+
+/// Returns `true` if the local copy of a document has the same size as the
+/// document and was modified after the document was last changed.
+fn is_up_to_date(document: &Document, path: &Path) -> bool {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return false,
+    };
+    let changed = SystemTime::from(document.date_changed);
+    metadata.len() == document.size
+        && metadata
+            .modified()
+            .is_ok_and(|modified| modified >= changed)
+}
+
+/// A course.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Course {
+    /// The name of the course's icon.
+    pub icon: String,
+    /// The course's identifier.
+    pub id: CourseId,
+    /// The modules that are enabled for the course.
+    #[serde(default)]
+    pub modules: CourseModules,
+    /// The course's name.
+    pub name: String,
+    /// The names of the course's teachers.
+    pub teachers: Vec<String>,
+}
+
+/// A course's identifier.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct CourseId(u64);
+
+impl CourseId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for CourseId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for CourseId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A student or teacher of a course.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseMember {
+    /// The member's class, or `None` for teachers.
+    pub class: Option<ClassId>,
+    /// The member's identifier.
+    pub id: UserId,
+    /// The member's full name.
+    pub name: String,
+    /// The member's role in the course.
+    pub role: MemberRole,
+}
+
+/// The modules that are enabled for a [`Course`](crate::courses::Course).
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CourseModules {
+    /// `true` if the course has a documents area.
+    pub documents: bool,
+    /// `true` if the course has a forum.
+    pub forum: bool,
+    /// `true` if the course has a links area.
+    pub links: bool,
+    /// `true` if the course's results are published in Skore.
+    pub results: bool,
+}
+
+/// A document in a course's documents area.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Document {
+    /// The date when the document was last changed.
+    pub date_changed: DateTime<FixedOffset>,
+    /// The document's identifier.
+    pub id: DocumentId,
+    /// The document's MIME type.
+    pub mime_type: String,
+    /// The document's name.
+    pub name: String,
+    /// The identifier of the document's parent folder, or `None` if the
+    /// document is in the root folder.
+    pub parent_id: Option<DocumentFolderId>,
+    /// The document's size in bytes.
+    pub size: u64,
+}
+
+/// A change to a course's documents area, as returned by
+/// [`DocumentTreeSnapshot::diff`](crate::courses::DocumentTreeSnapshot::diff).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum DocumentChange {
+    /// A document has been published.
+    Added(DocumentTreeEntry),
+    /// A document has been removed.
+    Removed(DocumentTreeEntry),
+    /// A document's contents have changed or the document has been renamed or
+    /// moved.
+    Updated {
+        /// The document before the change.
+        before: DocumentTreeEntry,
+        /// The document after the change.
+        after: DocumentTreeEntry,
+    },
+}
+
+/// A folder in a course's documents area.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentFolder {
+    /// The date when the folder was last changed.
+    pub date_changed: DateTime<FixedOffset>,
+    /// The folder's identifier.
+    pub id: DocumentFolderId,
+    /// The folder's name.
+    pub name: String,
+    /// The identifier of the folder's parent folder, or `None` if the folder is
+    /// in the root folder.
+    pub parent_id: Option<DocumentFolderId>,
+}
+
+/// A handle to a [`DocumentFolder`](crate::courses::DocumentFolder).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct DocumentFolderId(u64);
+
+impl DocumentFolderId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for DocumentFolderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for DocumentFolderId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A handle to a [`Document`](crate::courses::Document).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct DocumentId(u64);
+
+impl DocumentId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for DocumentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for DocumentId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A document in a course's documents area, along with the folders containing
+/// it.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentTreeEntry {
+    /// The document.
+    pub document: Document,
+    /// The names of the folders containing the document, from the root folder
+    /// down. Empty if the document is in the root folder.
+    pub folders: Vec<String>,
+}
+
+/// Every document in a course's documents area at a point in time, as returned
+/// by [`get_document_tree`](crate::courses::get_document_tree).
+///
+/// Snapshots can be serialized, so they can be stored between runs.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentTreeSnapshot {
+    /// The documents, sorted by path.
+    pub entries: Vec<DocumentTreeEntry>,
+}
+
+impl DocumentTreeSnapshot {
+    /// Compares this snapshot to a newer one and returns the changes, i.e. the
+    /// documents that have been added, removed or updated in between.
+    ///
+    /// Added and updated documents are returned first, in the order of the newer
+    /// snapshot, followed by removed documents.
+    pub fn diff(&self, newer: &DocumentTreeSnapshot) -> Vec<DocumentChange> {
+        let older: HashMap<_, _> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.document.id, entry))
+            .collect();
+
+        let mut changes = Vec::new();
+        for entry in &newer.entries {
+            match older.get(&entry.document.id) {
+                None => changes.push(DocumentChange::Added(entry.clone())),
+                Some(&before) if before != entry => changes.push(DocumentChange::Updated {
+                    before: before.clone(),
+                    after: entry.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+
+        let newer_ids: HashSet<_> = newer
+            .entries
+            .iter()
+            .map(|entry| entry.document.id)
+            .collect();
+        for entry in &self.entries {
+            if !newer_ids.contains(&entry.document.id) {
+                changes.push(DocumentChange::Removed(entry.clone()));
+            }
+        }
+        changes
+    }
+}
+
+/// A builder to download every document of a course, returned by
+/// [`download_all`](crate::courses::download_all).
+pub struct DownloadAll<'a, 'b> {
+    client: &'a Client<'b>,
+    concurrency: usize,
+    course_id: CourseId,
+    dir: PathBuf,
+    incremental: bool,
+}
+
+impl<'a, 'b> DownloadAll<'a, 'b> {
+    /// Sets the maximum number of documents that are downloaded concurrently.
+    /// Defaults to `4`.
+    pub fn concurrency(mut self, concurrency: usize) -> DownloadAll<'a, 'b> {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Skips documents that haven't changed since they were last downloaded,
+    /// i.e. whose local copy has the same size and was modified after the
+    /// document was last changed.
+    pub fn incremental(mut self) -> DownloadAll<'a, 'b> {
+        self.incremental = true;
+        self
+    }
+
+    /// Walks the course's documents area and downloads the documents.
+    ///
+    /// Documents are written to a temporary file first, so an interrupted
+    /// download never leaves a partial file behind under the document's name.
+    /// Documents whose names would end up at the same path, e.g. because a
+    /// folder contains two documents with the same name, are saved with their
+    /// identifier appended to the name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a folder can't be listed, a document can't be
+    /// downloaded or a file can't be written. Downloads that are in progress
+    /// when an error occurs are abandoned.
+    pub async fn execute(self) -> Result<DownloadSummary> {
+        let client = self.client;
+        let course_id = self.course_id;
+        let incremental = self.incremental;
+
+        let tree = get_document_tree(client, course_id).await?;
+        let mut paths = UniquePaths::default();
+        let documents: Vec<_> = tree
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let mut path = self.dir.clone();
+                for folder in &entry.folders {
+                    path.push(file::sanitize_name(folder));
+                }
+                path.push(file::sanitize_name(&entry.document.name));
+                let path = paths.file(path, entry.document.id);
+                (entry.document, path)
+            })
+            .collect();
+
+        stream::iter(documents)
+            .map(|(document, path)| async move {
+                if incremental && is_up_to_date(&document, &path) {
+                    return Ok(false);
+                }
+                let bytes = download_document(client, course_id, document.id).await?;
+                file::write(&path, bytes).await?;
+                Ok(true)
+            })
+            .buffer_unordered(self.concurrency)
+            .try_fold(
+                DownloadSummary::default(),
+                |mut summary, downloaded| async move {
+                    if downloaded {
+                        summary.downloaded += 1;
+                    } else {
+                        summary.skipped += 1;
+                    }
+                    Ok(summary)
+                },
+            )
+            .await
+    }
+}
+
+/// The outcome of [`DownloadAll::execute`](crate::courses::DownloadAll::execute).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DownloadSummary {
+    /// The number of documents that were downloaded.
+    pub downloaded: usize,
+    /// The number of documents that were skipped because they were up to date.
+    pub skipped: usize,
+}
+
+/// An attempt at a series of exercises.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExerciseAttempt {
+    /// The date when the attempt was finished.
+    pub finished_at: DateTime<FixedOffset>,
+    /// The maximum score.
+    pub max: f64,
+    /// The obtained score.
+    pub score: f64,
+    /// The identifier of the student who made the attempt.
+    pub student_id: UserId,
+    /// The full name of the student who made the attempt.
+    pub student_name: String,
+}
+
+/// A series of exercises, also known as a quiz.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExerciseSeries {
+    /// The date after which the series can no longer be attempted, or `None`
+    /// if there is none.
+    pub deadline: Option<DateTime<FixedOffset>>,
+    /// The series' identifier.
+    pub id: ExerciseSeriesId,
+    /// The maximum number of attempts per student, or `None` if the number of
+    /// attempts is unlimited.
+    pub max_attempts: Option<u32>,
+    /// The series' name.
+    pub name: String,
+    /// The number of exercises in the series.
+    pub question_count: u32,
+}
+
+/// A handle to a [`ExerciseSeries`](crate::courses::ExerciseSeries).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct ExerciseSeriesId(u64);
+
+impl ExerciseSeriesId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ExerciseSeriesId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for ExerciseSeriesId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Deserialize)]
+struct GetDocuments {
+    documents: Vec<Document>,
+    folders: Vec<DocumentFolder>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportFromMydoc {
+    file_id: FileId,
+    parent_id: Option<DocumentFolderId>,
+}
+
+/// The role of a [`CourseMember`](crate::courses::CourseMember).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MemberRole {
+    /// A teacher or co-teacher of the course.
+    Teacher,
+    /// A student taking the course.
+    Student,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NewDocumentFolder<'a> {
+    name: &'a str,
+    parent_id: Option<DocumentFolderId>,
+}
+
+/// An attachment of a [`NewsItem`](crate::courses::NewsItem).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewsAttachment {
+    /// The attachment's identifier.
+    pub id: NewsAttachmentId,
+    /// The attachment's MIME type.
+    pub mime_type: String,
+    /// The attachment's file name.
+    pub name: String,
+    /// The attachment's size in bytes.
+    pub size: u64,
+}
+
+/// A handle to a [`NewsAttachment`](crate::courses::NewsAttachment).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct NewsAttachmentId(u64);
+
+impl NewsAttachmentId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for NewsAttachmentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for NewsAttachmentId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A news item or announcement posted in a course.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewsItem {
+    /// The files attached to the news item.
+    #[serde(default)]
+    pub attachments: Vec<NewsAttachment>,
+    /// The name of the news item's author.
+    pub author: String,
+    /// The news item's body, formatted as HTML.
+    pub body: String,
+    /// The date when the news item was posted.
+    pub date: DateTime<FixedOffset>,
+    /// The news item's identifier.
+    pub id: NewsItemId,
+    /// The news item's title.
+    pub title: String,
+}
+
+/// A handle to a [`NewsItem`](crate::courses::NewsItem).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct NewsItemId(u64);
+
+impl NewsItemId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for NewsItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for NewsItemId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A student's submission in an uploadzone.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Submission {
+    /// The teacher's comment, or an empty string if there is none.
+    #[serde(default)]
+    pub comment: String,
+    /// The files that were handed in.
+    pub files: Vec<SubmissionFile>,
+    /// The grade recorded by the teacher, or an empty string if there is none.
+    #[serde(default)]
+    pub grade: String,
+    /// The submission's identifier.
+    pub id: SubmissionId,
+    /// `true` if the files were handed in after the deadline.
+    pub is_late: bool,
+    /// The identifier of the student who handed in the files.
+    pub student_id: UserId,
+    /// The full name of the student who handed in the files.
+    pub student_name: String,
+    /// The date when the student last handed in files.
+    pub submitted_at: DateTime<FixedOffset>,
+}
+
+/// A file in a [`Submission`](crate::courses::Submission).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmissionFile {
+    /// The file's MIME type.
+    pub mime_type: String,
+    /// The file's name.
+    pub name: String,
+    /// The file's size in bytes.
+    pub size: u64,
+}
+
+/// A handle to a [`Submission`](crate::courses::Submission).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct SubmissionId(u64);
+
+impl SubmissionId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for SubmissionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for SubmissionId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The status of the logged-in user's submission in an
+/// [`Uploadzone`](crate::courses::Uploadzone).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubmissionStatus {
+    /// Nothing has been handed in yet.
+    NotSubmitted,
+    /// Files have been handed in before the deadline.
+    Submitted,
+    /// Files have been handed in after the deadline.
+    SubmittedLate,
+}
+
+/// A place where students hand in assignments.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Uploadzone {
+    /// The deadline for submissions, or `None` if there is none.
+    pub deadline: Option<DateTime<FixedOffset>>,
+    /// The uploadzone's description, formatted as HTML.
+    #[serde(default)]
+    pub description: String,
+    /// The uploadzone's identifier.
+    pub id: UploadzoneId,
+    /// `true` if files can still be handed in.
+    pub is_open: bool,
+    /// The uploadzone's name.
+    pub name: String,
+    /// The status of the logged-in user's submission.
+    pub status: SubmissionStatus,
+    /// The date when the logged-in user last handed in files, or `None` if
+    /// nothing has been handed in yet.
+    pub submitted_at: Option<DateTime<FixedOffset>>,
+}
+
+/// A handle to a [`Uploadzone`](crate::courses::Uploadzone).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct UploadzoneId(u64);
+
+impl UploadzoneId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for UploadzoneId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for UploadzoneId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Serialize)]
+struct Visibility<'a> {
+    classes: &'a [ClassId],
+}
+
+/// A link in a course's weblinks area.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Weblink {
+    /// The link's description, or an empty string if there is none.
+    #[serde(default)]
+    pub description: String,
+    /// The link's identifier.
+    pub id: WeblinkId,
+    /// The link's title.
+    pub title: String,
+    /// The URL the link points to.
+    pub url: String,
+    /// The classes the link is visible for, or an empty vector if the link is
+    /// visible to every member of the course.
+    #[serde(default)]
+    pub visible_for: Vec<ClassId>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WeblinkForm<'a> {
+    description: &'a str,
+    title: &'a str,
+    url: &'a str,
+    visible_for: &'a [ClassId],
+}
+
+impl WeblinkForm<'_> {
+    fn validate(&self) -> Result<()> {
+        if self.title.trim().is_empty() {
+            return Err(Error::Validation {
+                field: "title",
+                reason: "must not be empty".to_owned(),
+            });
+        }
+        if let Err(err) = Url::parse(self.url) {
+            return Err(Error::Validation {
+                field: "url",
+                reason: format!("must be an absolute URL ({})", err),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A handle to a [`Weblink`](crate::courses::Weblink).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct WeblinkId(u64);
+
+impl WeblinkId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for WeblinkId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for WeblinkId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
@@ -0,0 +1,86 @@
+//! Formatting options for CSV exports.
+
+use crate::error::{Error, Result};
+use std::io::Write;
+
+/// The format of a CSV export.
+///
+/// The default format uses a comma as the delimiter and a period as the
+/// decimal separator. Spreadsheet applications using a Dutch or French locale,
+/// as is common in Belgium, expect the format returned by
+/// [`CsvFormat::flemish`](crate::csv::CsvFormat::flemish) instead.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CsvFormat {
+    /// The character separating the fields of a record.
+    pub delimiter: char,
+    /// The character separating the integer and fractional parts of numbers.
+    pub decimal_separator: char,
+}
+
+impl CsvFormat {
+    /// Returns a format using a semicolon as the delimiter and a comma as the
+    /// decimal separator.
+    pub fn flemish() -> Self {
+        Self {
+            delimiter: ';',
+            decimal_separator: ',',
+        }
+    }
+
+    /// Formats a number using the decimal separator.
+    pub(crate) fn number(&self, value: f64) -> String {
+        value
+            .to_string()
+            .replace('.', &self.decimal_separator.to_string())
+    }
+
+    /// Checks that the format produces records that can be parsed back.
+    pub(crate) fn validate(&self) -> Result<()> {
+        let reason = if matches!(self.delimiter, '"' | '\r' | '\n') {
+            "must not be a quote or a line break"
+        } else if self.delimiter == self.decimal_separator {
+            "must differ from the decimal separator"
+        } else {
+            return Ok(());
+        };
+        Err(Error::Validation {
+            field: "delimiter",
+            reason: reason.to_owned(),
+        })
+    }
+
+    /// Writes a record, quoting the fields that contain the delimiter, quotes
+    /// or line breaks as described in RFC 4180.
+    pub(crate) fn write_record<W: Write, T: AsRef<str>>(
+        &self,
+        writer: &mut W,
+        fields: &[T],
+    ) -> Result<()> {
+        let mut record = String::new();
+        for (index, field) in fields.iter().enumerate() {
+            if index > 0 {
+                record.push(self.delimiter);
+            }
+            let field = field.as_ref();
+            if field.contains(&[self.delimiter, '"', '\r', '\n'][..]) {
+                record.push('"');
+                record.push_str(&field.replace('"', "\"\""));
+                record.push('"');
+            } else {
+                record.push_str(field);
+            }
+        }
+        record.push_str("\r\n");
+        writer.write_all(record.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            decimal_separator: '.',
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! A summary of what's new across modules, e.g. for daily summary emails.
+
+use crate::{
+    courses::{self, DocumentTreeEntry},
+    error::Result,
+    messages::{self, MailBox, Message},
+    mydoc::{self, File},
+    notifications::{self, Notification},
+    results::{self, Evaluation, ResultsFilter},
+    Client,
+};
+use chrono::{DateTime, FixedOffset};
+use futures::{future, stream, StreamExt, TryStreamExt};
+
+/// Collects everything that's new since the specified date into a digest.
+///
+/// Recently changed personal files, inbox messages, results, course documents
+/// and notifications are fetched concurrently.
+///
+/// # Errors
+///
+/// Returns an error if one of the modules can't be queried.
+pub async fn collect(client: &Client<'_>, since: DateTime<FixedOffset>) -> Result<Digest> {
+    const CONCURRENCY: usize = 4;
+
+    let files = async {
+        let files = mydoc::get_recent_files(client).await?;
+        Ok(files
+            .into_iter()
+            .filter(|file| file.date_changed >= since)
+            .map(|file| DigestEntry {
+                date: file.date_changed,
+                item: DigestItem::File(file),
+            })
+            .collect::<Vec<_>>())
+    };
+
+    let messages = messages::stream(client, MailBox::Inbox)
+        .try_take_while(|message| future::ok(message.date >= since))
+        .map_ok(|message| DigestEntry {
+            date: message.date,
+            item: DigestItem::Message(message),
+        })
+        .try_collect::<Vec<_>>();
+
+    let results = async {
+        let filter = ResultsFilter::new().since(since.date_naive());
+        let evaluations = results::get_results(client, &filter).await?;
+        Ok(evaluations
+            .into_iter()
+            .map(|evaluation| DigestEntry {
+                // Results only have a date, so they're sorted as if they were
+                // published at midnight.
+                date: evaluation
+                    .date
+                    .and_hms_opt(0, 0, 0)
+                    .and_then(|date| date.and_local_timezone(*since.offset()).single())
+                    .unwrap_or(since),
+                item: DigestItem::Result(evaluation),
+            })
+            .collect::<Vec<_>>())
+    };
+
+    let documents = async {
+        let courses = courses::get_courses(client).await?;
+        stream::iter(
+            courses
+                .into_iter()
+                .filter(|course| course.modules.documents),
+        )
+        .map(|course| async move {
+            let tree = courses::get_document_tree(client, course.id).await?;
+            Ok(tree
+                .entries
+                .into_iter()
+                .filter(|entry| entry.document.date_changed >= since)
+                .map(|entry| DigestEntry {
+                    date: entry.document.date_changed,
+                    item: DigestItem::CourseDocument {
+                        course: course.name.clone(),
+                        entry,
+                    },
+                })
+                .collect::<Vec<_>>())
+        })
+        .buffer_unordered(CONCURRENCY)
+        .try_concat()
+        .await
+    };
+
+    let notifications = async {
+        let notifications = notifications::get(client).await?;
+        Ok(notifications
+            .into_iter()
+            .filter(|notification| notification.date >= since)
+            .map(|notification| DigestEntry {
+                date: notification.date,
+                item: DigestItem::Notification(notification),
+            })
+            .collect::<Vec<_>>())
+    };
+
+    let (files, messages, results, documents, notifications) =
+        future::try_join5(files, messages, results, documents, notifications).await?;
+    let mut entries: Vec<DigestEntry> = files
+        .into_iter()
+        .chain(messages)
+        .chain(results)
+        .chain(documents)
+        .chain(notifications)
+        .collect();
+    entries.sort_by_key(|entry| entry.date);
+    Ok(Digest { entries, since })
+}
+
+/// Everything that's new across modules since a point in time, as returned by
+/// [`collect`](crate::digest::collect).
+#[derive(Clone, Debug)]
+pub struct Digest {
+    /// The new items, sorted by date in ascending order.
+    pub entries: Vec<DigestEntry>,
+    /// The date since which items are included.
+    pub since: DateTime<FixedOffset>,
+}
+
+impl Digest {
+    /// Returns `true` if nothing is new.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A new item in a [`Digest`](crate::digest::Digest).
+#[derive(Clone, Debug)]
+pub struct DigestEntry {
+    /// The date when the item was created or changed.
+    pub date: DateTime<FixedOffset>,
+    /// The item.
+    pub item: DigestItem,
+}
+
+/// An item in a [`DigestEntry`](crate::digest::DigestEntry).
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum DigestItem {
+    /// A document that has been published or updated in a course.
+    CourseDocument {
+        /// The name of the course.
+        course: String,
+        /// The document, along with the folders containing it.
+        entry: DocumentTreeEntry,
+    },
+    /// A personal file that has been changed.
+    File(File),
+    /// A message that has arrived in the inbox.
+    Message(Message),
+    /// A notification.
+    Notification(Notification),
+    /// A result that has been published.
+    Result(Evaluation),
+}
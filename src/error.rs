@@ -1,33 +1,566 @@
 //! Error handling functionality.
 
+use crate::serde::deserialize_optional_id;
 use reqwest::{Error as ReqwestError, StatusCode};
-use std::{error::Error as StdError, fmt};
+use serde::Deserialize;
+use serde_json::Error as JsonError;
+use std::{error::Error as StdError, fmt, io::Error as IoError};
 
 /// An error returned by the `smartschool` crate.
+///
+/// This enum may gain new variants in the future, so matching on it requires a
+/// wildcard arm. Errors are `Send` and `Sync`, which allows them to be used
+/// with multi-threaded executors and error-wrapping crates.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
+    /// An error reported by the API in a structured error response.
+    Api(ApiError),
     /// An authentication failure, most likely due to invalid login credentials.
     Authentication,
+    /// A response body that couldn't be deserialized, most likely because the
+    /// server changed the shape of its response.
+    Decode {
+        /// The URL of the request that produced the response.
+        url: String,
+        /// The beginning of the response body, truncated to a reasonable
+        /// length.
+        snippet: String,
+        /// The underlying deserialization error.
+        source: JsonError,
+    },
+    /// An error returned by an external system, like a task synchronization
+    /// adapter.
+    External(Box<dyn StdError + Send + Sync>),
+    /// An I/O error, for example while writing exported data.
+    Io(IoError),
+    /// A failure to reach the server.
+    Network {
+        /// The kind of network failure.
+        kind: NetworkErrorKind,
+        /// The underlying error returned by the [`reqwest`](reqwest) crate.
+        source: ReqwestError,
+    },
+    /// The requested item doesn't exist.
+    NotFound {
+        /// The kind of the requested item.
+        kind: EntityKind,
+        /// The identifier of the requested item.
+        id: String,
+    },
     /// An error returned by the [`reqwest`](reqwest) crate.
     Reqwest(ReqwestError),
+    /// The session has expired, so the client needs to log in again.
+    ///
+    /// The server signals this by redirecting API requests to the login page.
+    SessionExpired,
     /// An HTTP error response.
-    StatusCode(StatusCode),
+    Status {
+        /// The status code of the response.
+        code: StatusCode,
+        /// The URL of the request that produced the response.
+        url: String,
+        /// The beginning of the response body, truncated to a reasonable
+        /// length.
+        body_snippet: String,
+    },
+    /// A client-side precondition failure, detected before sending a request.
+    Validation {
+        /// The name of the offending argument or field.
+        field: &'static str,
+        /// A human-readable description of the failed precondition.
+        reason: String,
+    },
+}
+
+impl Error {
+    /// Converts a `404 Not Found` response into an
+    /// [`Error::NotFound`](crate::Error::NotFound) for the requested item. Other
+    /// errors are returned unchanged.
+    pub(crate) fn for_entity<T: ToString>(self, kind: EntityKind, id: T) -> Self {
+        match self {
+            Error::Api(ApiError {
+                code: KnownErrorCode::NotFound,
+                ..
+            }) => Error::NotFound {
+                kind,
+                id: id.to_string(),
+            },
+            Error::Status { code, .. } if code == StatusCode::NOT_FOUND => Error::NotFound {
+                kind,
+                id: id.to_string(),
+            },
+            err => err,
+        }
+    }
+
+    /// Converts a `404 Not Found` response that identifies the requested item
+    /// into an [`Error::NotFound`](crate::Error::NotFound) for it. Other errors,
+    /// including `404 Not Found` responses that don't identify the missing
+    /// item, are returned unchanged.
+    ///
+    /// This is used for requests that refer to several items, such as a source
+    /// and a destination, where a bare `404 Not Found` doesn't tell which item
+    /// is missing.
+    pub(crate) fn for_identified_entity<T: ToString>(self, kind: EntityKind, id: T) -> Self {
+        let id = id.to_string();
+        match self {
+            Error::Api(ApiError {
+                code: KnownErrorCode::NotFound,
+                item_id: Some(ref item_id),
+                ..
+            }) if *item_id == id => Error::NotFound { kind, id },
+            err => err,
+        }
+    }
+
+    /// Returns `true` if the error is caused by a failed login attempt or an
+    /// expired session. Logging in again might resolve the error.
+    pub fn is_auth(&self) -> bool {
+        match self {
+            Error::Authentication | Error::SessionExpired => true,
+            Error::Status { code, .. } => *code == StatusCode::UNAUTHORIZED,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the requested item doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Error::Api(err) => err.code == KnownErrorCode::NotFound,
+            Error::NotFound { .. } => true,
+            Error::Status { code, .. } => *code == StatusCode::NOT_FOUND,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the error is likely to be transient, meaning that
+    /// retrying the same request later might succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Network { .. } => true,
+            Error::Status { code, .. } => {
+                code.is_server_error()
+                    || *code == StatusCode::REQUEST_TIMEOUT
+                    || *code == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self)
+        match self {
+            Error::Api(err) => fmt::Display::fmt(err, f),
+            Error::Authentication => write!(
+                f,
+                "authentication failed, most likely due to invalid login credentials"
+            ),
+            Error::Decode { url, .. } => write!(f, "failed to decode response from {}", url),
+            Error::External(_) => write!(f, "external error"),
+            Error::Io(_) => write!(f, "I/O error"),
+            Error::Network { kind, .. } => fmt::Display::fmt(kind, f),
+            Error::NotFound { kind, id } => write!(f, "{} `{}` doesn't exist", kind, id),
+            Error::Reqwest(_) => write!(f, "HTTP client error"),
+            Error::SessionExpired => write!(f, "session expired, log in again to continue"),
+            Error::Status {
+                code,
+                url,
+                body_snippet,
+            } => {
+                write!(f, "{} returned {}", url, code)?;
+                if !body_snippet.is_empty() {
+                    write!(f, ": {}", body_snippet)?;
+                }
+                Ok(())
+            }
+            Error::Validation { field, reason } => write!(f, "invalid `{}`: {}", field, reason),
+        }
+    }
+}
+
+impl From<IoError> for Error {
+    fn from(err: IoError) -> Self {
+        Error::Io(err)
     }
 }
 
 impl From<ReqwestError> for Error {
     fn from(err: ReqwestError) -> Self {
-        Error::Reqwest(err)
+        match NetworkErrorKind::classify(&err) {
+            Some(kind) => Error::Network { kind, source: err },
+            None => Error::Reqwest(err),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Decode { source, .. } => Some(source),
+            Error::External(err) => Some(err.as_ref()),
+            Error::Io(err) => Some(err),
+            Error::Network { source, .. } => Some(source),
+            Error::Reqwest(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// Fails to compile if `Error` ever stops being `Send` or `Sync`.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Error>();
+};
+
+/// A structured error response returned by the API.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq)]
+pub struct ApiError {
+    /// The error code.
+    #[serde(alias = "error")]
+    pub code: KnownErrorCode,
+    /// The identifier of the item the error refers to, if the API specifies
+    /// one, e.g. the missing item of a `not_found` error.
+    #[serde(
+        default,
+        rename = "itemId",
+        deserialize_with = "deserialize_optional_id"
+    )]
+    pub item_id: Option<String>,
+    /// A human-readable description of the error, usually in Dutch.
+    #[serde(default)]
+    pub message: String,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.message.is_empty() {
+            write!(f, "API error `{}`", self.code.as_str())
+        } else {
+            write!(f, "API error `{}`: {}", self.code.as_str(), self.message)
+        }
+    }
+}
+
+/// An error code returned by the API.
+///
+/// Codes that aren't (yet) known to this crate are preserved in the
+/// [`Unknown`](crate::error::KnownErrorCode::Unknown) variant.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(from = "String")]
+pub enum KnownErrorCode {
+    /// The user doesn't have permission to perform the action.
+    PermissionDenied,
+    /// The user's storage quota has been exceeded.
+    QuotaExceeded,
+    /// The provided name is illegal.
+    InvalidName,
+    /// The provided email address is malformed.
+    InvalidEmail,
+    /// The provided phone number is malformed.
+    InvalidPhone,
+    /// The item is locked, for example because another user is editing it.
+    ItemLocked,
+    /// The item doesn't exist.
+    NotFound,
+    /// An error code that isn't known to this crate.
+    Unknown(String),
+}
+
+impl KnownErrorCode {
+    /// Returns the error code as it is returned by the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            KnownErrorCode::PermissionDenied => "permission_denied",
+            KnownErrorCode::QuotaExceeded => "quota_exceeded",
+            KnownErrorCode::InvalidName => "invalid_name",
+            KnownErrorCode::InvalidEmail => "invalid_email",
+            KnownErrorCode::InvalidPhone => "invalid_phone",
+            KnownErrorCode::ItemLocked => "item_locked",
+            KnownErrorCode::NotFound => "not_found",
+            KnownErrorCode::Unknown(code) => code,
+        }
+    }
+}
+
+impl From<String> for KnownErrorCode {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "permission_denied" | "access_denied" => KnownErrorCode::PermissionDenied,
+            "quota_exceeded" => KnownErrorCode::QuotaExceeded,
+            "invalid_name" => KnownErrorCode::InvalidName,
+            "invalid_email" => KnownErrorCode::InvalidEmail,
+            "invalid_phone" | "invalid_phone_number" => KnownErrorCode::InvalidPhone,
+            "item_locked" | "locked" => KnownErrorCode::ItemLocked,
+            "not_found" => KnownErrorCode::NotFound,
+            _ => KnownErrorCode::Unknown(code),
+        }
+    }
+}
+
+/// The kind of an item referred to by an
+/// [`Error::NotFound`](crate::Error::NotFound).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum EntityKind {
+    /// An absence registered by the school.
+    Absence,
+    /// A class of students.
+    Class,
+    /// A co-account of a user, used by parents to log in.
+    CoAccount,
+    /// A course.
+    Course,
+    /// A graded evaluation in the results.
+    Evaluation,
+    /// A series of exercises in a course.
+    ExerciseSeries,
+    /// A file in the virtual file system.
+    File,
+    /// A folder in the virtual file system.
+    Folder,
+    /// A survey or questionnaire.
+    Form,
+    /// An evaluation column in the teacher-side gradebook.
+    GradebookColumn,
+    /// A lesson in the agenda.
+    Lesson,
+    /// A message in the messaging system.
+    Message,
+    /// A news item or announcement.
+    NewsItem,
+    /// A notification in the notification feed.
+    Notification,
+    /// A profile picture.
+    Picture,
+    /// An item in the planner.
+    PlannedItem,
+    /// A published term report.
+    Report,
+    /// A revision of a file in the virtual file system.
+    Revision,
+    /// A submission in an uploadzone.
+    Submission,
+    /// A ticket filed with the helpdesk.
+    Ticket,
+    /// An uploadzone where students hand in assignments.
+    Uploadzone,
+    /// A user of the platform.
+    User,
+    /// A link in a course's weblinks area.
+    Weblink,
+}
+
+impl fmt::Display for EntityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntityKind::Absence => write!(f, "absence"),
+            EntityKind::Class => write!(f, "class"),
+            EntityKind::CoAccount => write!(f, "co-account"),
+            EntityKind::Course => write!(f, "course"),
+            EntityKind::Evaluation => write!(f, "evaluation"),
+            EntityKind::ExerciseSeries => write!(f, "exercise series"),
+            EntityKind::File => write!(f, "file"),
+            EntityKind::Folder => write!(f, "folder"),
+            EntityKind::Form => write!(f, "form"),
+            EntityKind::GradebookColumn => write!(f, "gradebook column"),
+            EntityKind::Lesson => write!(f, "lesson"),
+            EntityKind::Message => write!(f, "message"),
+            EntityKind::NewsItem => write!(f, "news item"),
+            EntityKind::Notification => write!(f, "notification"),
+            EntityKind::Picture => write!(f, "profile picture"),
+            EntityKind::PlannedItem => write!(f, "planned item"),
+            EntityKind::Report => write!(f, "report"),
+            EntityKind::Revision => write!(f, "revision"),
+            EntityKind::Submission => write!(f, "submission"),
+            EntityKind::Ticket => write!(f, "ticket"),
+            EntityKind::Uploadzone => write!(f, "uploadzone"),
+            EntityKind::User => write!(f, "user"),
+            EntityKind::Weblink => write!(f, "weblink"),
+        }
     }
 }
 
-impl StdError for Error {}
+/// The kind of an [`Error::Network`](crate::Error::Network).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum NetworkErrorKind {
+    /// The server's address couldn't be resolved.
+    Dns,
+    /// The connection to the server couldn't be established, for example
+    /// because it was refused.
+    Connect,
+    /// The request timed out.
+    Timeout,
+}
+
+impl NetworkErrorKind {
+    /// Determines the kind of network failure that caused a
+    /// [`reqwest`](reqwest) error, if any.
+    fn classify(err: &ReqwestError) -> Option<Self> {
+        if err.is_timeout() {
+            Some(NetworkErrorKind::Timeout)
+        } else if err.is_connect() {
+            // Resolver failures aren't exposed as a distinct type, but `hyper`
+            // consistently labels them in its error message.
+            let mut source = err.source();
+            while let Some(err) = source {
+                if err.to_string().starts_with("dns error") {
+                    return Some(NetworkErrorKind::Dns);
+                }
+                source = err.source();
+            }
+            Some(NetworkErrorKind::Connect)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for NetworkErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkErrorKind::Dns => write!(f, "failed to resolve host"),
+            NetworkErrorKind::Connect => write!(f, "failed to connect"),
+            NetworkErrorKind::Timeout => write!(f, "request timed out"),
+        }
+    }
+}
 
 /// A specialized [`Result`](std::result::Result) type returned by the
 /// `smartschool` crate.
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Client;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn display_api() {
+        let err = Error::Api(ApiError {
+            code: KnownErrorCode::QuotaExceeded,
+            item_id: None,
+            message: "Quota overschreden".to_owned(),
+        });
+        assert_eq!(
+            err.to_string(),
+            "API error `quota_exceeded`: Quota overschreden"
+        );
+
+        let err = Error::Api(ApiError {
+            code: KnownErrorCode::Unknown("foo".to_owned()),
+            item_id: None,
+            message: String::new(),
+        });
+        assert_eq!(err.to_string(), "API error `foo`");
+    }
+
+    #[test]
+    fn display_authentication() {
+        assert_eq!(
+            Error::Authentication.to_string(),
+            "authentication failed, most likely due to invalid login credentials"
+        );
+    }
+
+    #[test]
+    fn display_decode() {
+        let err = Error::Decode {
+            url: "https://example.com/api".to_owned(),
+            snippet: "x".to_owned(),
+            source: serde_json::from_str::<u8>("x").unwrap_err(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "failed to decode response from https://example.com/api"
+        );
+    }
+
+    #[test]
+    fn display_external() {
+        let err = Error::External("calendar unavailable".into());
+        assert_eq!(err.to_string(), "external error");
+    }
+
+    #[test]
+    fn display_io() {
+        let err = Error::from(IoError::new(ErrorKind::NotFound, "file not found"));
+        assert_eq!(err.to_string(), "I/O error");
+    }
+
+    #[tokio::test]
+    async fn display_network() {
+        // Nothing should be listening on port 1.
+        let err: Error = Client::new()
+            .get("http://127.0.0.1:1")
+            .send()
+            .await
+            .unwrap_err()
+            .into();
+        match &err {
+            Error::Network { kind, .. } => {
+                assert_eq!(*kind, NetworkErrorKind::Connect);
+                assert_eq!(err.to_string(), "failed to connect");
+            }
+            _ => panic!("expected a network error, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn display_not_found() {
+        let err = Error::NotFound {
+            kind: EntityKind::Folder,
+            id: "trashed".to_owned(),
+        };
+        assert_eq!(err.to_string(), "folder `trashed` doesn't exist");
+    }
+
+    #[test]
+    fn display_reqwest() {
+        let source = Client::new().get("not a url").build().unwrap_err();
+        assert_eq!(Error::from(source).to_string(), "HTTP client error");
+    }
+
+    #[test]
+    fn display_session_expired() {
+        assert_eq!(
+            Error::SessionExpired.to_string(),
+            "session expired, log in again to continue"
+        );
+    }
+
+    #[test]
+    fn display_status() {
+        let err = Error::Status {
+            code: StatusCode::INTERNAL_SERVER_ERROR,
+            url: "https://example.com/api".to_owned(),
+            body_snippet: "Oops".to_owned(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "https://example.com/api returned 500 Internal Server Error: Oops"
+        );
+
+        let err = Error::Status {
+            code: StatusCode::BAD_GATEWAY,
+            url: "https://example.com/api".to_owned(),
+            body_snippet: String::new(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "https://example.com/api returned 502 Bad Gateway"
+        );
+    }
+
+    #[test]
+    fn display_validation() {
+        let err = Error::Validation {
+            field: "new_name",
+            reason: "must not contain `:`".to_owned(),
+        };
+        assert_eq!(err.to_string(), "invalid `new_name`: must not contain `:`");
+    }
+}
@@ -0,0 +1,182 @@
+//! Utilities for writing downloaded items to the local file system.
+
+use crate::error::Result;
+use bytes::Bytes;
+use futures::{
+    channel::{mpsc, oneshot},
+    executor, SinkExt, Stream, TryStreamExt,
+};
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    thread,
+};
+
+/// Replaces the characters that aren't allowed in file names on common
+/// platforms.
+pub fn sanitize_name(name: &str) -> String {
+    const ILLEGAL_CHARS: &[char] = &['/', ':', '*', '?', '"', '\\', '<', '>', '|'];
+
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if ILLEGAL_CHARS.contains(&c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    match name.trim() {
+        "" | "." | ".." => "_".to_owned(),
+        name => name.to_owned(),
+    }
+}
+
+/// Streams a download to a file at the specified path, creating the parent
+/// folders if needed.
+///
+/// The download is written to a temporary file first, which is removed again
+/// if the download or the write fails, so an interrupted download never leaves
+/// a partial file behind. The file is written on a separate thread, so the
+/// executor is never blocked.
+pub async fn write<S>(path: &Path, body: S) -> Result<()>
+where
+    S: Stream<Item = Result<Bytes>>,
+{
+    // The number of chunks that are buffered while the file is being written.
+    const CAPACITY: usize = 16;
+
+    let path = path.to_owned();
+    let (mut chunks_tx, chunks_rx) = mpsc::channel(CAPACITY);
+    let (result_tx, result_rx) = oneshot::channel();
+    thread::spawn(move || {
+        let result = write_chunks(&path, executor::block_on_stream(chunks_rx));
+        let _ = result_tx.send(result);
+    });
+
+    futures::pin_mut!(body);
+    let downloaded = loop {
+        match body.try_next().await {
+            Ok(Some(chunk)) => {
+                if chunks_tx.send(Some(chunk)).await.is_err() {
+                    // The file couldn't be written, the error is returned below.
+                    break Ok(());
+                }
+            }
+            Ok(None) => {
+                let _ = chunks_tx.send(None).await;
+                break Ok(());
+            }
+            Err(err) => break Err(err),
+        }
+    };
+    // Wait for the writer, so the temporary file is removed before returning
+    // if the download failed.
+    drop(chunks_tx);
+    let written = result_rx
+        .await
+        .unwrap_or_else(|_| Err(io::Error::other("file writer panicked")));
+    downloaded?;
+    written?;
+    Ok(())
+}
+
+/// Writes the chunks received from [`write`] to a temporary file next to the
+/// path, and renames it to the path once `None` is received.
+///
+/// The temporary file is removed if the chunks end before `None` is received,
+/// which means the download was abandoned, or if it can't be written.
+fn write_chunks<I: Iterator<Item = Option<Bytes>>>(path: &Path, chunks: I) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let partial = path.with_file_name(format!(
+        "{}.part",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let result = write_partial(&partial, chunks).and_then(|completed| {
+        if completed {
+            fs::rename(&partial, path).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    });
+    if !matches!(result, Ok(true)) {
+        let _ = fs::remove_file(&partial);
+    }
+    result.map(|_| ())
+}
+
+/// Writes the chunks to the temporary file and returns whether `None` was
+/// received, i.e. whether the download completed.
+fn write_partial<I: Iterator<Item = Option<Bytes>>>(partial: &Path, chunks: I) -> io::Result<bool> {
+    let mut file = fs::File::create(partial)?;
+    for chunk in chunks {
+        match chunk {
+            Some(chunk) => file.write_all(&chunk)?,
+            None => {
+                file.flush()?;
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Keeps track of the paths that items are written to, so items whose names
+/// are sanitized to the same path are never written to the same file.
+///
+/// Paths are compared case-insensitively, as file systems on common platforms
+/// are.
+#[derive(Debug, Default)]
+pub struct UniquePaths(HashSet<String>);
+
+impl UniquePaths {
+    /// Returns the path a file should be written to, which is the specified
+    /// path if it isn't taken yet, or the path with the file's identifier
+    /// appended to its name, before the extension, otherwise.
+    pub fn file<T: fmt::Display>(&mut self, mut path: PathBuf, id: T) -> PathBuf {
+        if !self.claim(&path) {
+            let name = match (path.file_stem(), path.extension()) {
+                (Some(stem), Some(extension)) => format!(
+                    "{} ({}).{}",
+                    stem.to_string_lossy(),
+                    id,
+                    extension.to_string_lossy()
+                ),
+                _ => format!(
+                    "{} ({})",
+                    path.file_name().unwrap_or_default().to_string_lossy(),
+                    id
+                ),
+            };
+            path.set_file_name(name);
+            self.claim(&path);
+        }
+        path
+    }
+
+    /// Returns the path a folder should be written to, which is the specified
+    /// path if it isn't taken yet, or the path with the folder's identifier
+    /// appended to its name otherwise.
+    pub fn folder<T: fmt::Display>(&mut self, mut path: PathBuf, id: T) -> PathBuf {
+        if !self.claim(&path) {
+            let name = format!(
+                "{} ({})",
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                id
+            );
+            path.set_file_name(name);
+            self.claim(&path);
+        }
+        path
+    }
+
+    /// Marks a path as taken and returns `true` if it wasn't taken before.
+    fn claim(&mut self, path: &Path) -> bool {
+        self.0.insert(path.to_string_lossy().to_lowercase())
+    }
+}
@@ -0,0 +1,444 @@
+//! Forms, i.e. surveys and questionnaires filled in by users.
+
+use crate::{
+    csv::CsvFormat,
+    error::{EntityKind, Error, Result},
+    http::{TryJson, TrySend},
+    user::UserId,
+    Client,
+};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, io::Write};
+
+/// Fetches the responses to a form and writes them to a CSV file, one record
+/// per response, preceded by a header record containing the questions' texts.
+///
+/// The selected options of multiple-choice questions are written as their
+/// labels, separated by semicolons. Unanswered questions and the respondents
+/// of anonymous forms are left empty.
+///
+/// # Errors
+///
+/// Returns an error if the format is invalid, the responses can't be fetched
+/// or the writer fails.
+pub async fn export_csv<W: Write>(
+    client: &Client<'_>,
+    form_id: FormId,
+    format: CsvFormat,
+    mut writer: W,
+) -> Result<()> {
+    format.validate()?;
+    let results = get_results(client, form_id).await?;
+
+    let mut header = vec!["Date".to_owned(), "Respondent".to_owned()];
+    header.extend(
+        results
+            .questions
+            .iter()
+            .map(|question| question.text.clone()),
+    );
+    format.write_record(&mut writer, &header)?;
+
+    for response in &results.responses {
+        let mut record = vec![
+            response.date.to_rfc3339(),
+            response
+                .respondent
+                .as_ref()
+                .map(|respondent| respondent.name.clone())
+                .unwrap_or_default(),
+        ];
+        record.extend(results.questions.iter().map(|question| {
+            match response.answers.get(&question.id) {
+                Some(Answer::Choice(choices)) => {
+                    let options: &[Choice] = match &question.kind {
+                        QuestionKind::Choice { options, .. } => options,
+                        _ => &[],
+                    };
+                    choices
+                        .iter()
+                        .map(|id| {
+                            options
+                                .iter()
+                                .find(|option| option.id == *id)
+                                .map(|option| option.label.clone())
+                                .unwrap_or_else(|| id.to_string())
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                }
+                Some(Answer::Scale(value)) => value.to_string(),
+                Some(Answer::Text(text)) => text.clone(),
+                None => String::new(),
+            }
+        }));
+        format.write_record(&mut writer, &record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Returns the forms assigned to the logged-in user, sorted by deadline in
+/// ascending order. Forms without a deadline come last.
+pub async fn get_forms(client: &Client<'_>) -> Result<Vec<Form>> {
+    let url = format!("{}/forms/api/v1/forms", client.url());
+    let mut forms: Vec<Form> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    forms.sort_by_key(|form| (form.deadline.is_none(), form.deadline));
+    Ok(forms)
+}
+
+/// Returns the questions of a form, in the order they're displayed.
+///
+/// # Errors
+///
+/// Returns an error if the form doesn't exist or isn't assigned to the
+/// logged-in user.
+pub async fn get_questions(client: &Client<'_>, form_id: FormId) -> Result<Vec<Question>> {
+    let url = format!("{}/forms/api/v1/forms/{}/questions", client.url(), form_id);
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Form, form_id))?
+        .try_json()
+        .await
+}
+
+/// Returns the questions of a form along with every response to it, for the
+/// form's owner.
+///
+/// # Errors
+///
+/// Returns an error if the form doesn't exist or the logged-in user isn't
+/// allowed to view its responses.
+pub async fn get_results(client: &Client<'_>, form_id: FormId) -> Result<FormResults> {
+    let url = format!("{}/forms/api/v1/forms/{}/results", client.url(), form_id);
+    let mut results: FormResults = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Form, form_id))?
+        .try_json()
+        .await?;
+    results.responses.sort_by_key(|response| response.date);
+    Ok(results)
+}
+
+/// Submits the logged-in user's answers to a form, keyed by question.
+///
+/// The answers are checked against the form's questions before they're
+/// submitted, so every required question has to be answered and every answer
+/// has to fit its question.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * A required question isn't answered.
+/// * An answer doesn't fit its question, e.g. because it refers to an option
+///   that doesn't exist or a value outside of the scale.
+/// * An answer refers to a question that isn't part of the form.
+/// * The form doesn't exist or can no longer be filled in.
+pub async fn submit(
+    client: &Client<'_>,
+    form_id: FormId,
+    answers: &HashMap<QuestionId, Answer>,
+) -> Result<()> {
+    let questions = get_questions(client, form_id).await?;
+    validate_answers(&questions, answers)?;
+
+    let entries: Vec<AnswerEntry> = questions
+        .iter()
+        .filter_map(|question| {
+            answers.get(&question.id).map(|answer| AnswerEntry {
+                answer,
+                question: question.id,
+            })
+        })
+        .collect();
+
+    let url = format!("{}/forms/api/v1/forms/{}/responses", client.url(), form_id);
+    client
+        .http_client()
+        .post(&url)
+        .json(&entries)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Form, form_id))?;
+    Ok(())
+}
+
+/// Checks that answers fit a form's questions.
+fn validate_answers(questions: &[Question], answers: &HashMap<QuestionId, Answer>) -> Result<()> {
+    let invalid = |reason: String| Error::Validation {
+        field: "answers",
+        reason,
+    };
+
+    for id in answers.keys() {
+        if !questions.iter().any(|question| question.id == *id) {
+            return Err(invalid(format!(
+                "must only answer questions of the form, not question `{}`",
+                id
+            )));
+        }
+    }
+
+    for question in questions {
+        let answer = match answers.get(&question.id) {
+            Some(answer) => answer,
+            None if question.is_required => {
+                return Err(invalid(format!(
+                    "must answer required question `{}`",
+                    question.id
+                )));
+            }
+            None => continue,
+        };
+        let fits = match (&question.kind, answer) {
+            (QuestionKind::Choice { multiple, options }, Answer::Choice(choices)) => {
+                (*multiple || choices.len() <= 1)
+                    && !(question.is_required && choices.is_empty())
+                    && choices
+                        .iter()
+                        .all(|choice| options.iter().any(|option| option.id == *choice))
+            }
+            (QuestionKind::Scale { max, min, .. }, Answer::Scale(value)) => {
+                (*min..=*max).contains(value)
+            }
+            (QuestionKind::Text, Answer::Text(text)) => {
+                !(question.is_required && text.trim().is_empty())
+            }
+            _ => false,
+        };
+        if !fits {
+            return Err(invalid(format!(
+                "must answer question `{}` with an answer that fits it",
+                question.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// An answer to a [`Question`](crate::forms::Question).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum Answer {
+    /// The selected options of a multiple-choice question.
+    Choice(Vec<ChoiceId>),
+    /// The selected value of a question answered on a scale.
+    Scale(i32),
+    /// The answer to an open question.
+    Text(String),
+}
+
+#[derive(Serialize)]
+struct AnswerEntry<'a> {
+    answer: &'a Answer,
+    question: QuestionId,
+}
+
+/// An option of a multiple-choice question.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Choice {
+    /// The option's identifier.
+    pub id: ChoiceId,
+    /// The option's label.
+    pub label: String,
+}
+
+/// A handle to a [`Choice`](crate::forms::Choice).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct ChoiceId(u64);
+
+impl ChoiceId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ChoiceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for ChoiceId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A survey or questionnaire.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Form {
+    /// The date after which the form can no longer be filled in, or `None` if
+    /// there is none.
+    pub deadline: Option<DateTime<FixedOffset>>,
+    /// The form's description, or an empty string if it has none.
+    #[serde(default)]
+    pub description: String,
+    /// The form's identifier.
+    pub id: FormId,
+    /// The full name of the user who created the form.
+    pub owner: String,
+    /// Whether the logged-in user has filled in the form.
+    pub status: FormStatus,
+    /// The form's title.
+    pub title: String,
+}
+
+/// A handle to a [`Form`](crate::forms::Form).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct FormId(u64);
+
+impl FormId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for FormId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for FormId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The responses to a form, as returned by
+/// [`get_results`](crate::forms::get_results).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormResults {
+    /// The form's questions, in the order they're displayed.
+    pub questions: Vec<Question>,
+    /// The responses, sorted by date in ascending order.
+    pub responses: Vec<Response>,
+}
+
+/// The completion status of a [`Form`](crate::forms::Form) for the logged-in
+/// user.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FormStatus {
+    /// The form hasn't been opened yet.
+    NotStarted,
+    /// The form has been partially filled in, but not submitted.
+    InProgress,
+    /// The form has been submitted.
+    Completed,
+    /// A status that isn't known to this crate.
+    #[serde(other)]
+    Other,
+}
+
+/// A question of a [`Form`](crate::forms::Form).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Question {
+    /// The question's identifier.
+    pub id: QuestionId,
+    /// Whether the question has to be answered.
+    pub is_required: bool,
+    /// The kind of the question, including the answers it accepts.
+    #[serde(flatten)]
+    pub kind: QuestionKind,
+    /// The question's text.
+    pub text: String,
+}
+
+/// A handle to a [`Question`](crate::forms::Question).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct QuestionId(u64);
+
+impl QuestionId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for QuestionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for QuestionId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The kind of a [`Question`](crate::forms::Question).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum QuestionKind {
+    /// A multiple-choice question, answered with
+    /// [`Answer::Choice`](crate::forms::Answer::Choice).
+    Choice {
+        /// Whether several options can be selected.
+        #[serde(default)]
+        multiple: bool,
+        /// The options that can be selected.
+        options: Vec<Choice>,
+    },
+    /// A question answered on a scale of integers, answered with
+    /// [`Answer::Scale`](crate::forms::Answer::Scale).
+    Scale {
+        /// The highest value of the scale.
+        max: i32,
+        /// The lowest value of the scale.
+        min: i32,
+    },
+    /// An open question, answered with
+    /// [`Answer::Text`](crate::forms::Answer::Text).
+    Text,
+    /// A kind of question that isn't known to this crate, which can't be
+    /// answered.
+    #[serde(other)]
+    Other,
+}
+
+/// The user who filled in a [`Response`](crate::forms::Response).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Respondent {
+    /// The user's identifier.
+    pub id: UserId,
+    /// The user's full name.
+    pub name: String,
+}
+
+/// A response to a [`Form`](crate::forms::Form).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    /// The answers, keyed by question. Questions that weren't answered are
+    /// missing.
+    pub answers: HashMap<QuestionId, Answer>,
+    /// The date when the response was submitted.
+    pub date: DateTime<FixedOffset>,
+    /// The user who filled in the form, or `None` if the form is anonymous.
+    pub respondent: Option<Respondent>,
+}
@@ -0,0 +1,516 @@
+//! The helpdesk, through which users report problems to the school's ICT
+//! service.
+
+use crate::{
+    error::{EntityKind, Error, Result},
+    http::{TryJson, TrySend},
+    serde::Json,
+    upload::{self, File, UploadDirectory},
+    Client,
+};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+/// Closes a ticket, e.g. because the problem solved itself.
+///
+/// # Errors
+///
+/// Returns an error if the ticket doesn't exist or the logged-in user isn't
+/// allowed to close it.
+pub async fn close(client: &Client<'_>, id: TicketId) -> Result<()> {
+    let url = format!("{}/helpdesk/api/v1/tickets/{}/close", client.url(), id);
+    client
+        .http_client()
+        .post(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Ticket, id))?;
+    Ok(())
+}
+
+/// Files a ticket with the helpdesk and returns its identifier.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The ticket's title or description is empty.
+/// * One of the files has an [illegal file
+///   name](crate::upload::upload_file).
+/// * The category doesn't exist.
+pub async fn create_ticket(client: &Client<'_>, ticket: TicketBuilder) -> Result<TicketId> {
+    if ticket.title.trim().is_empty() {
+        return Err(Error::Validation {
+            field: "title",
+            reason: "must not be empty".to_owned(),
+        });
+    }
+    if ticket.description.trim().is_empty() {
+        return Err(Error::Validation {
+            field: "description",
+            reason: "must not be empty".to_owned(),
+        });
+    }
+
+    let upload_dir = if ticket.files.is_empty() {
+        None
+    } else {
+        let upload_dir = upload::get_upload_directory(client).await?;
+        for file in ticket.files {
+            upload::upload_file(client, upload_dir.clone(), file).await?;
+        }
+        Some(upload_dir)
+    };
+
+    let new_ticket = NewTicket {
+        category: ticket.category,
+        description: &ticket.description,
+        fields: &ticket.fields,
+        item: ticket.item,
+        priority: ticket.priority,
+        title: &ticket.title,
+        upload_dir,
+    };
+    let url = format!("{}/helpdesk/api/v1/tickets", client.url());
+    let response: CreateTicket = client
+        .http_client()
+        .post(&url)
+        .json(&new_ticket)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    Ok(response.id)
+}
+
+/// Returns the categories tickets can be filed in, as configured by the
+/// school, including their items and the fields tickets in them require.
+pub async fn get_categories(client: &Client<'_>) -> Result<Vec<Category>> {
+    let url = format!("{}/helpdesk/api/v1/categories", client.url());
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await
+}
+
+/// Returns a ticket, including its reply thread.
+///
+/// # Errors
+///
+/// Returns an error if the ticket doesn't exist or the logged-in user isn't
+/// allowed to view it.
+pub async fn get_ticket(client: &Client<'_>, id: TicketId) -> Result<Ticket> {
+    let url = format!("{}/helpdesk/api/v1/tickets/{}", client.url(), id);
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Ticket, id))?
+        .try_json()
+        .await
+}
+
+/// Returns the tickets filed by the logged-in user that match a filter, sorted
+/// by the date of their last update in descending order.
+///
+/// The returned tickets don't include their reply thread; use
+/// [`get_ticket`](crate::helpdesk::get_ticket) to fetch it.
+pub async fn get_tickets(client: &Client<'_>, filter: &TicketFilter) -> Result<Vec<Ticket>> {
+    let url = format!("{}/helpdesk/api/v1/tickets", client.url());
+    let mut tickets: Vec<Ticket> = client
+        .http_client()
+        .get(&url)
+        .query(filter)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    tickets.sort_by_key(|ticket| Reverse(ticket.date_updated));
+    Ok(tickets)
+}
+
+/// Reopens a closed or resolved ticket, e.g. because the problem came back.
+///
+/// # Errors
+///
+/// Returns an error if the ticket doesn't exist or the logged-in user isn't
+/// allowed to reopen it.
+pub async fn reopen(client: &Client<'_>, id: TicketId) -> Result<()> {
+    let url = format!("{}/helpdesk/api/v1/tickets/{}/reopen", client.url(), id);
+    client
+        .http_client()
+        .post(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Ticket, id))?;
+    Ok(())
+}
+
+/// Adds a reply to a ticket's thread, optionally along with files.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The text is empty.
+/// * One of the files has an [illegal file
+///   name](crate::upload::upload_file).
+/// * The ticket doesn't exist or the logged-in user isn't allowed to reply to
+///   it.
+pub async fn reply(
+    client: &Client<'_>,
+    ticket_id: TicketId,
+    text: &str,
+    attachments: Vec<File>,
+) -> Result<()> {
+    if text.trim().is_empty() {
+        return Err(Error::Validation {
+            field: "text",
+            reason: "must not be empty".to_owned(),
+        });
+    }
+
+    let upload_dir = if attachments.is_empty() {
+        None
+    } else {
+        let upload_dir = upload::get_upload_directory(client).await?;
+        for file in attachments {
+            upload::upload_file(client, upload_dir.clone(), file).await?;
+        }
+        Some(upload_dir)
+    };
+
+    let mut form = HashMap::new();
+    form.insert("body", Json::Str(text));
+    if let Some(upload_dir) = &upload_dir {
+        form.insert("uploadDir", Json::Str(upload_dir.as_str()));
+    }
+
+    let url = format!(
+        "{}/helpdesk/api/v1/tickets/{}/replies",
+        client.url(),
+        ticket_id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Ticket, ticket_id))?;
+    Ok(())
+}
+
+/// A category of tickets, like hardware or network problems.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Category {
+    /// The additional fields tickets in the category can or must fill in.
+    /// Empty if the category was returned as part of a ticket.
+    #[serde(default)]
+    pub fields: Vec<Field>,
+    /// The category's identifier.
+    pub id: CategoryId,
+    /// The items within the category that a ticket can be about, like the
+    /// printers of the school. Empty if the category was returned as part of a
+    /// ticket.
+    #[serde(default)]
+    pub items: Vec<Item>,
+    /// The category's name.
+    pub name: String,
+}
+
+/// A handle to a [`Category`](crate::helpdesk::Category).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct CategoryId(u64);
+
+impl CategoryId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for CategoryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for CategoryId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Deserialize)]
+struct CreateTicket {
+    id: TicketId,
+}
+
+/// An additional field of a [`Category`](crate::helpdesk::Category), like the
+/// room where a problem occurs.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Field {
+    /// Whether tickets in the category must fill in the field.
+    pub is_required: bool,
+    /// The field's human-readable label.
+    pub label: String,
+    /// The field's name, as passed to
+    /// [`TicketBuilder::field`](crate::helpdesk::TicketBuilder::field).
+    pub name: String,
+}
+
+/// An item within a [`Category`](crate::helpdesk::Category).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    /// The item's identifier.
+    pub id: ItemId,
+    /// The item's name.
+    pub name: String,
+}
+
+/// A handle to a [`Item`](crate::helpdesk::Item).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct ItemId(u64);
+
+impl ItemId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for ItemId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NewTicket<'a> {
+    category: CategoryId,
+    description: &'a str,
+    fields: &'a BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    item: Option<ItemId>,
+    priority: Priority,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upload_dir: Option<UploadDirectory>,
+}
+
+/// The priority of a [`Ticket`](crate::helpdesk::Ticket).
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum Priority {
+    /// A problem that can wait.
+    Low,
+    /// A problem with normal priority.
+    #[default]
+    Normal,
+    /// A problem that needs to be solved quickly.
+    High,
+    /// A problem that blocks work and needs to be solved immediately.
+    Urgent,
+}
+
+/// A reply in a ticket's thread.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reply {
+    /// The names of the files attached to the reply.
+    #[serde(default)]
+    pub attachments: Vec<String>,
+    /// The full name of the reply's author.
+    pub author: String,
+    /// The reply's text.
+    pub body: String,
+    /// The date when the reply was posted.
+    pub date: DateTime<FixedOffset>,
+}
+
+/// A ticket filed with the helpdesk.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ticket {
+    /// The full name of the staff member handling the ticket, or `None` if it
+    /// hasn't been assigned yet.
+    pub assignee: Option<String>,
+    /// The ticket's category.
+    pub category: Category,
+    /// The date when the ticket was filed.
+    pub date_created: DateTime<FixedOffset>,
+    /// The date when the ticket was last updated, e.g. by a reply.
+    pub date_updated: DateTime<FixedOffset>,
+    /// The ticket's description.
+    pub description: String,
+    /// The ticket's identifier.
+    pub id: TicketId,
+    /// The ticket's priority.
+    pub priority: Priority,
+    /// The replies to the ticket, sorted by date in ascending order. Empty if
+    /// the ticket was returned by [`get_tickets`](crate::helpdesk::get_tickets).
+    #[serde(default)]
+    pub replies: Vec<Reply>,
+    /// The ticket's status.
+    pub status: TicketStatus,
+    /// The ticket's title.
+    pub title: String,
+}
+
+/// A builder to construct a ticket, as filed by
+/// [`create_ticket`](crate::helpdesk::create_ticket).
+pub struct TicketBuilder {
+    category: CategoryId,
+    description: String,
+    fields: BTreeMap<String, String>,
+    files: Vec<File>,
+    item: Option<ItemId>,
+    priority: Priority,
+    title: String,
+}
+
+impl TicketBuilder {
+    /// Creates a ticket with normal priority in a category, with the specified
+    /// title and description and without any files.
+    pub fn new<T, U>(category_id: CategoryId, title: T, description: U) -> TicketBuilder
+    where
+        T: Into<String>,
+        U: Into<String>,
+    {
+        TicketBuilder {
+            category: category_id,
+            description: description.into(),
+            fields: BTreeMap::new(),
+            files: Vec::new(),
+            item: None,
+            priority: Priority::default(),
+            title: title.into(),
+        }
+    }
+
+    /// Fills in one of the category's additional
+    /// [fields](crate::helpdesk::Category::fields), identified by its name.
+    pub fn field<T, U>(mut self, name: T, value: U) -> TicketBuilder
+    where
+        T: Into<String>,
+        U: Into<String>,
+    {
+        self.fields.insert(name.into(), value.into());
+        self
+    }
+
+    /// Attaches a file to the ticket, like a screenshot or a log file. Can be
+    /// called multiple times to attach several files.
+    pub fn file(mut self, file: File) -> TicketBuilder {
+        self.files.push(file);
+        self
+    }
+
+    /// Sets the item within the category that the ticket is about.
+    pub fn item(mut self, item_id: ItemId) -> TicketBuilder {
+        self.item = Some(item_id);
+        self
+    }
+
+    /// Sets the ticket's priority.
+    pub fn priority(mut self, priority: Priority) -> TicketBuilder {
+        self.priority = priority;
+        self
+    }
+}
+
+/// A filter used to [`get_tickets`](crate::helpdesk::get_tickets).
+///
+/// Filters are combined, so a ticket has to match all of them to be returned.
+/// An empty filter matches every ticket.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category_id: Option<CategoryId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<TicketStatus>,
+}
+
+impl TicketFilter {
+    /// Creates an empty filter.
+    pub fn new() -> TicketFilter {
+        TicketFilter::default()
+    }
+
+    /// Only matches tickets in the specified category.
+    pub fn category(mut self, category_id: CategoryId) -> TicketFilter {
+        self.category_id = Some(category_id);
+        self
+    }
+
+    /// Only matches tickets with the specified status.
+    pub fn status(mut self, status: TicketStatus) -> TicketFilter {
+        self.status = Some(status);
+        self
+    }
+}
+
+/// A handle to a [`Ticket`](crate::helpdesk::Ticket).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct TicketId(u64);
+
+impl TicketId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for TicketId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for TicketId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The status of a [`Ticket`](crate::helpdesk::Ticket).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TicketStatus {
+    /// The ticket has been filed, but nobody has looked at it yet.
+    New,
+    /// The ticket is being handled.
+    Open,
+    /// The helpdesk is waiting for a reply from the ticket's submitter.
+    Waiting,
+    /// The problem has been solved.
+    Resolved,
+    /// The ticket has been closed.
+    Closed,
+    /// A status that isn't known to this crate.
+    #[serde(other)]
+    Other,
+}
@@ -1,8 +1,65 @@
 //! HTTP-related utilities.
 
-use crate::error::{Error, Result};
+use crate::error::{ApiError, Error, Result};
 use futures::future::BoxFuture;
-use reqwest::{RequestBuilder, Response};
+use reqwest::{
+    header::{CONTENT_TYPE, LOCATION},
+    RequestBuilder, Response, StatusCode,
+};
+use serde::de::DeserializeOwned;
+
+/// The maximum length of a response body snippet included in an error.
+const SNIPPET_LEN: usize = 512;
+
+/// Percent-encodes an identifier for use as a single segment of a URL path, so
+/// that characters like `/`, `?` or `#` can't change the request's target.
+///
+/// Every byte except the unreserved characters of RFC 3986 is encoded.
+pub fn encode_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(char::from(byte))
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Truncates a response body to at most [`SNIPPET_LEN`] bytes without
+/// splitting a UTF-8 character.
+pub fn snippet(body: &str) -> String {
+    if body.len() <= SNIPPET_LEN {
+        return body.to_owned();
+    }
+    let mut end = SNIPPET_LEN;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    body[..end].to_owned()
+}
+
+/// Returns `true` if the response redirects to the login page, which is what
+/// the server does when the session has expired.
+fn is_login_redirect(response: &Response) -> bool {
+    response.status().is_redirection()
+        && response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .is_some_and(|location| location.contains("/login"))
+}
+
+/// Returns `true` if the response body is an HTML page.
+fn is_html(response: &Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/html"))
+}
 
 /// Adds a custom sending method to
 /// [`RequestBuilder`](reqwest::RequestBuilder)s.
@@ -15,12 +72,61 @@ impl TrySend for RequestBuilder {
     fn try_send(self) -> BoxFuture<'static, Result<Response>> {
         Box::pin(async {
             let response = self.send().await?;
-            let status = response.status();
-            if status.is_client_error() || status.is_server_error() {
-                Err(Error::StatusCode(status))
+            let code = response.status();
+            if is_login_redirect(&response) {
+                Err(Error::SessionExpired)
+            } else if code.is_client_error() || code.is_server_error() {
+                let url = response.url().to_string();
+                let body = response.text().await.unwrap_or_default();
+                // Authentication and authorization failures are reported with
+                // their status code, even if the body describes them, so an
+                // expired login is still recognized by `Error::is_auth`.
+                let is_auth_failure =
+                    code == StatusCode::UNAUTHORIZED || code == StatusCode::FORBIDDEN;
+                if !is_auth_failure {
+                    if let Ok(err) = serde_json::from_str::<ApiError>(&body) {
+                        return Err(Error::Api(err));
+                    }
+                }
+                Err(Error::Status {
+                    code,
+                    url,
+                    body_snippet: snippet(&body),
+                })
             } else {
                 Ok(response)
             }
         })
     }
 }
+
+/// Adds a custom JSON deserialization method to
+/// [`Response`](reqwest::Response)s.
+pub trait TryJson {
+    // TODO: Use async trait method
+    fn try_json<T>(self) -> BoxFuture<'static, Result<T>>
+    where
+        T: DeserializeOwned + Send + 'static;
+}
+
+impl TryJson for Response {
+    fn try_json<T>(self) -> BoxFuture<'static, Result<T>>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        Box::pin(async {
+            // API endpoints only ever respond with JSON. An HTML page means that
+            // we've been served the login page instead.
+            if is_html(&self) {
+                return Err(Error::SessionExpired);
+            }
+            let url = self.url().to_string();
+            let body = self.bytes().await?;
+            serde_json::from_slice(&body).map_err(|source| Error::Decode {
+                url,
+                snippet: snippet(&String::from_utf8_lossy(&body)),
+                source,
+            })
+        })
+    }
+}
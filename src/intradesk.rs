@@ -0,0 +1,551 @@
+//! The Intradesk, a school-wide document area for policy documents, forms and
+//! other shared files.
+//!
+//! Unlike the personal files in [`mydoc`](crate::mydoc), items in the Intradesk
+//! carry permissions that are managed by the school.
+
+use crate::{
+    error::{EntityKind, Error, Result},
+    file::{self, UniquePaths},
+    http::{TryJson, TrySend},
+    mydoc, poll,
+    serde::Json,
+    upload::UploadDirectory,
+    user::GroupId,
+    Client,
+};
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset};
+use futures::{
+    stream::{BoxStream, Stream},
+    AsyncRead, TryStreamExt,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Creates a folder in an Intradesk folder, or in the Intradesk's root folder
+/// if `None` is specified, and returns the created folder.
+///
+/// Only staff members with the right permissions are allowed to create
+/// folders.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The folder name is [illegal](crate::mydoc::rename_file).
+/// * The parent folder doesn't exist or the logged-in user isn't allowed to
+///   add items to it.
+pub async fn create_folder(
+    client: &Client<'_>,
+    parent_id: Option<FolderId>,
+    name: &str,
+) -> Result<Folder> {
+    mydoc::validate_name("name", name)?;
+
+    let folder = NewFolder { name, parent_id };
+    let url = format!("{}/intradesk/api/v1/folders", client.url());
+    client
+        .http_client()
+        .post(&url)
+        .json(&folder)
+        .try_send()
+        .await
+        .map_err(|err| match parent_id {
+            Some(id) => err.for_entity(EntityKind::Folder, id),
+            None => err,
+        })?
+        .try_json()
+        .await
+}
+
+/// Downloads an Intradesk file and returns its contents as a non-blocking
+/// reader.
+///
+/// # Errors
+///
+/// Returns an error if the file doesn't exist or the logged-in user isn't
+/// allowed to view it. Errors that occur while reading are returned as
+/// [`io::Error`](std::io::Error)s wrapping an [`Error`](crate::Error).
+pub async fn download(client: &Client<'_>, file_id: FileId) -> Result<impl AsyncRead> {
+    Ok(download_bytes(client, file_id)
+        .await?
+        .map_err(io::Error::other)
+        .into_async_read())
+}
+
+/// Downloads an Intradesk file to the specified path.
+///
+/// The file is written to a temporary file first, so an interrupted download
+/// never leaves a partial file behind at the path.
+///
+/// # Errors
+///
+/// Returns an error if the file doesn't exist, the download fails or the file
+/// can't be written.
+pub async fn download_to_path<P: AsRef<Path>>(
+    client: &Client<'_>,
+    file_id: FileId,
+    path: P,
+) -> Result<()> {
+    let bytes = download_bytes(client, file_id).await?;
+    file::write(path.as_ref(), bytes).await
+}
+
+/// Returns the contents of an Intradesk folder, or of the Intradesk's root
+/// folder if `None` is specified, sorted by name.
+///
+/// Items the logged-in user isn't allowed to view aren't returned.
+///
+/// # Errors
+///
+/// Returns an error if the folder doesn't exist.
+pub async fn get_listing(client: &Client<'_>, folder: Option<FolderId>) -> Result<Listing> {
+    let url = match folder {
+        Some(id) => format!("{}/intradesk/api/v1/directory-listing/{}", client.url(), id),
+        None => format!("{}/intradesk/api/v1/directory-listing", client.url()),
+    };
+    let mut listing: Listing = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| match folder {
+            Some(id) => err.for_entity(EntityKind::Folder, id),
+            None => err,
+        })?
+        .try_json()
+        .await?;
+    listing.files.sort_by(|a, b| a.name.cmp(&b.name));
+    listing.folders.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(listing)
+}
+
+/// Downloads every file in the Intradesk that the logged-in user is allowed to
+/// view to a folder, recreating the Intradesk's folder structure, and returns
+/// the paths of the written files.
+///
+/// The identifier of a file or folder is appended to its name if another item
+/// in the same folder has the same name.
+///
+/// # Errors
+///
+/// Returns an error if a folder can't be listed, a file can't be downloaded or
+/// a file can't be written.
+pub async fn mirror<P: AsRef<Path>>(client: &Client<'_>, dir: P) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut unique_paths = UniquePaths::default();
+    let mut folders = vec![(None, dir.as_ref().to_path_buf())];
+    while let Some((folder, dir)) = folders.pop() {
+        let listing = get_listing(client, folder).await?;
+        for entry in listing.files {
+            let path = unique_paths.file(dir.join(file::sanitize_name(&entry.name)), entry.id);
+            download_to_path(client, entry.id, &path).await?;
+            paths.push(path);
+        }
+        for folder in listing.folders {
+            let path = unique_paths.folder(dir.join(file::sanitize_name(&folder.name)), folder.id);
+            folders.push((Some(folder.id), path));
+        }
+    }
+    Ok(paths)
+}
+
+/// Replaces the contents of an Intradesk file with the file in an upload
+/// directory, keeping its identifier and permissions, and returns the updated
+/// file.
+///
+/// Use the [`upload`](crate::upload) module to fill the upload directory with
+/// exactly one file.
+///
+/// # Errors
+///
+/// Returns an error if the file doesn't exist, the logged-in user isn't
+/// allowed to edit it or the upload directory is invalid.
+pub async fn replace_file(
+    client: &Client<'_>,
+    file_id: FileId,
+    upload_dir: &UploadDirectory,
+) -> Result<File> {
+    let mut form = HashMap::new();
+    form.insert("uploadDir", Json::Str(upload_dir.as_str()));
+
+    let url = format!(
+        "{}/intradesk/api/v1/files/{}/versions",
+        client.url(),
+        file_id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::File, file_id))?
+        .try_json()
+        .await
+}
+
+/// Searches the Intradesk for files and folders whose name contains the
+/// specified string, and returns them sorted by name.
+///
+/// Items the logged-in user isn't allowed to view aren't returned.
+///
+/// # Errors
+///
+/// Returns an error if the query is empty.
+pub async fn search(client: &Client<'_>, query: &str) -> Result<Listing> {
+    if query.trim().is_empty() {
+        return Err(Error::Validation {
+            field: "query",
+            reason: "must not be empty".to_owned(),
+        });
+    }
+
+    let url = format!("{}/intradesk/api/v1/search", client.url());
+    let mut listing: Listing = client
+        .http_client()
+        .get(&url)
+        .query(&[("query", query)])
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    listing.files.sort_by(|a, b| a.name.cmp(&b.name));
+    listing.folders.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(listing)
+}
+
+/// Restricts an Intradesk file to the specified groups of users. An empty
+/// slice makes the file visible to everyone who can view its folder.
+///
+/// # Errors
+///
+/// Returns an error if the file doesn't exist or the logged-in user isn't
+/// allowed to edit it.
+pub async fn set_file_target_groups(
+    client: &Client<'_>,
+    file_id: FileId,
+    groups: &[GroupId],
+) -> Result<()> {
+    let url = format!(
+        "{}/intradesk/api/v1/files/{}/target-groups",
+        client.url(),
+        file_id
+    );
+    client
+        .http_client()
+        .put(&url)
+        .json(&TargetGroups { groups })
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::File, file_id))?;
+    Ok(())
+}
+
+/// Restricts an Intradesk folder and its contents to the specified groups of
+/// users. An empty slice makes the folder visible to everyone who can view its
+/// parent folder.
+///
+/// # Errors
+///
+/// Returns an error if the folder doesn't exist or the logged-in user isn't
+/// allowed to edit it.
+pub async fn set_folder_target_groups(
+    client: &Client<'_>,
+    folder_id: FolderId,
+    groups: &[GroupId],
+) -> Result<()> {
+    let url = format!(
+        "{}/intradesk/api/v1/folders/{}/target-groups",
+        client.url(),
+        folder_id
+    );
+    client
+        .http_client()
+        .put(&url)
+        .json(&TargetGroups { groups })
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Folder, folder_id))?;
+    Ok(())
+}
+
+/// Adds the files in an upload directory to an Intradesk folder, or to the
+/// Intradesk's root folder if `None` is specified, and returns the created
+/// files.
+///
+/// Use the [`upload`](crate::upload) module to fill the upload directory.
+///
+/// # Errors
+///
+/// Returns an error if the folder doesn't exist, the logged-in user isn't
+/// allowed to add files to it or the upload directory is invalid.
+pub async fn upload(
+    client: &Client<'_>,
+    folder_id: Option<FolderId>,
+    upload_dir: &UploadDirectory,
+) -> Result<Vec<File>> {
+    let files = UploadFiles {
+        parent_id: folder_id,
+        upload_dir,
+    };
+    let url = format!("{}/intradesk/api/v1/files/upload", client.url());
+    client
+        .http_client()
+        .post(&url)
+        .json(&files)
+        .try_send()
+        .await
+        .map_err(|err| match folder_id {
+            Some(id) => err.for_entity(EntityKind::Folder, id),
+            None => err,
+        })?
+        .try_json()
+        .await
+}
+
+/// Returns a non-blocking stream of files that are added to or updated in the
+/// Intradesk, polling the server at the specified interval.
+///
+/// The files fetched when the stream is first polled serve as the baseline and
+/// aren't yielded. Every subsequent fetch of the whole Intradesk is compared to
+/// the previous one and the differences are yielded in the order of the files'
+/// change dates. Errors are yielded as they occur, after which polling
+/// continues at the same interval, so the stream never ends by itself.
+pub fn watch<'a>(client: &'a Client<'_>, interval: Duration) -> BoxStream<'a, Result<FileChange>> {
+    poll::every(
+        interval,
+        None,
+        move |previous: Option<HashMap<FileId, File>>| async move {
+            match get_all_files(client).await {
+                Ok(current) => {
+                    let changes = match &previous {
+                        Some(previous) => diff_files(previous, &current),
+                        None => Vec::new(),
+                    };
+                    (Some(current), Ok(changes))
+                }
+                Err(err) => (previous, Err(err)),
+            }
+        },
+    )
+}
+
+/// Compares two fetches of the Intradesk and returns the added and updated
+/// files, sorted by change date.
+fn diff_files(
+    previous: &HashMap<FileId, File>,
+    current: &HashMap<FileId, File>,
+) -> Vec<FileChange> {
+    let mut changes: Vec<FileChange> = current
+        .values()
+        .filter_map(|file| match previous.get(&file.id) {
+            None => Some(FileChange::Added(file.clone())),
+            Some(before)
+                if before.date_changed != file.date_changed || before.size != file.size =>
+            {
+                Some(FileChange::Updated {
+                    before: before.clone(),
+                    after: file.clone(),
+                })
+            }
+            Some(_) => None,
+        })
+        .collect();
+    changes.sort_by_key(|change| change.file().date_changed);
+    changes
+}
+
+/// Downloads an Intradesk file and returns its contents as a stream of bytes.
+async fn download_bytes(
+    client: &Client<'_>,
+    file_id: FileId,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let url = format!(
+        "{}/intradesk/api/v1/files/{}/download",
+        client.url(),
+        file_id
+    );
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::File, file_id))?;
+    Ok(response.bytes_stream().err_into())
+}
+
+/// Walks the whole Intradesk and returns every file the logged-in user is
+/// allowed to view.
+async fn get_all_files(client: &Client<'_>) -> Result<HashMap<FileId, File>> {
+    let mut files = HashMap::new();
+    let mut folders = vec![None];
+    while let Some(folder) = folders.pop() {
+        let listing = get_listing(client, folder).await?;
+        files.extend(listing.files.into_iter().map(|file| (file.id, file)));
+        folders.extend(listing.folders.into_iter().map(|folder| Some(folder.id)));
+    }
+    Ok(files)
+}
+
+/// A file in the Intradesk.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct File {
+    /// The date when the file was last changed.
+    pub date_changed: DateTime<FixedOffset>,
+    /// The file's identifier.
+    pub id: FileId,
+    /// The file's MIME type.
+    pub mime_type: String,
+    /// The file's name.
+    pub name: String,
+    /// The full name of the user who last changed the file.
+    #[serde(default)]
+    pub owner: String,
+    /// What the logged-in user is allowed to do with the file.
+    pub permissions: Permissions,
+    /// The file's size in bytes.
+    pub size: u64,
+}
+
+/// A change to the Intradesk, as yielded by
+/// [`watch`](crate::intradesk::watch).
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum FileChange {
+    /// A file has been added.
+    Added(File),
+    /// A file's contents have been replaced.
+    Updated {
+        /// The file before the update.
+        before: File,
+        /// The file after the update.
+        after: File,
+    },
+}
+
+impl FileChange {
+    /// Returns the file in its current state.
+    pub fn file(&self) -> &File {
+        match self {
+            FileChange::Added(file) => file,
+            FileChange::Updated { after, .. } => after,
+        }
+    }
+}
+
+/// A handle to a [`File`](crate::intradesk::File).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct FileId(u64);
+
+impl FileId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for FileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for FileId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A folder in the Intradesk.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Folder {
+    /// The date when the folder was last changed.
+    pub date_changed: DateTime<FixedOffset>,
+    /// The folder's identifier.
+    pub id: FolderId,
+    /// The folder's name.
+    pub name: String,
+    /// What the logged-in user is allowed to do with the folder.
+    pub permissions: Permissions,
+}
+
+/// A handle to a [`Folder`](crate::intradesk::Folder).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct FolderId(u64);
+
+impl FolderId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for FolderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for FolderId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The contents of an Intradesk folder, as returned by
+/// [`get_listing`](crate::intradesk::get_listing), or the results of a
+/// [`search`](crate::intradesk::search).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Listing {
+    /// The files, sorted by name.
+    #[serde(default)]
+    pub files: Vec<File>,
+    /// The folders, sorted by name.
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NewFolder<'a> {
+    name: &'a str,
+    parent_id: Option<FolderId>,
+}
+
+/// What the logged-in user is allowed to do with an item in the Intradesk.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Permissions {
+    /// Whether the user is allowed to delete the item.
+    #[serde(default)]
+    pub can_delete: bool,
+    /// Whether the user is allowed to rename or replace the item.
+    #[serde(default)]
+    pub can_edit: bool,
+    /// Whether the user is allowed to add files to the folder. Always `false`
+    /// for files.
+    #[serde(default)]
+    pub can_upload: bool,
+}
+
+#[derive(Serialize)]
+struct TargetGroups<'a> {
+    groups: &'a [GroupId],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadFiles<'a> {
+    parent_id: Option<FolderId>,
+    upload_dir: &'a UploadDirectory,
+}
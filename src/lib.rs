@@ -34,9 +34,28 @@
 pub use client::Client;
 pub use error::Error;
 
+pub mod absences;
+pub mod account;
+pub mod agenda;
 pub mod client;
+pub mod courses;
+pub mod csv;
+pub mod digest;
 pub mod error;
+mod file;
+pub mod forms;
+pub mod helpdesk;
 mod http;
+pub mod intradesk;
+pub mod messages;
 pub mod mydoc;
+pub mod news;
+pub mod notifications;
+pub mod planner;
+mod poll;
+pub mod results;
 mod serde;
+pub mod skore;
+pub mod tasks;
 pub mod upload;
+pub mod user;
@@ -0,0 +1,1380 @@
+//! The internal messaging system.
+
+use crate::{
+    error::{EntityKind, Error, Result},
+    file,
+    http::{TryJson, TrySend},
+    poll,
+    serde::Json,
+    upload::{self, File, UploadDirectory},
+    user::{ClassId, GroupId, UserId},
+    Client,
+};
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset};
+use futures::{
+    stream::{self, BoxStream},
+    Stream, StreamExt, TryStreamExt,
+};
+use regex::Regex;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Write},
+    path::Path,
+    sync::LazyLock,
+    time::Duration,
+};
+
+/// Returns a builder to perform state changes on many messages at once.
+///
+/// # Example
+///
+/// ```ignore
+/// let outcomes = messages::bulk(&client)
+///     .mark_read(ids.clone())
+///     .move_to(ids, folder_id)
+///     .execute()
+///     .await;
+/// ```
+pub fn bulk<'a, 'b>(client: &'a Client<'b>) -> Bulk<'a, 'b> {
+    Bulk {
+        client,
+        concurrency: 4,
+        operations: Vec::new(),
+    }
+}
+
+/// Permanently deletes a message. If you want to trash the message instead,
+/// use [`move_to_trash`](crate::messages::move_to_trash).
+///
+/// # Errors
+///
+/// Returns an error if the message doesn't exist.
+pub async fn delete_forever(client: &Client<'_>, id: MessageId) -> Result<()> {
+    let url = format!("{}/messages/api/v1/messages/{}", client.url(), id);
+    client
+        .http_client()
+        .delete(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Message, id))?;
+    Ok(())
+}
+
+/// Downloads a message's attachment and returns its contents as a
+/// non-blocking stream of [`Bytes`](bytes::Bytes).
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The message doesn't exist.
+/// * The attachment doesn't exist or isn't associated with the message.
+pub async fn download_attachment(
+    client: &Client<'_>,
+    message_id: MessageId,
+    attachment_id: AttachmentId,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let url = format!(
+        "{}/messages/api/v1/messages/{}/attachments/{}/download",
+        client.url(),
+        message_id,
+        attachment_id
+    );
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_identified_entity(EntityKind::Message, message_id))?;
+    Ok(response.bytes_stream().err_into())
+}
+
+/// Exports every message in a mailbox, including attachments, to a directory
+/// of `.eml` files that can be opened by regular mail clients.
+///
+/// Each message is written to a file named after its identifier, like
+/// `12345.eml`. Existing files are overwritten. Senders and recipients are
+/// given email addresses of the form `{user-id}@{instance-host}`, since
+/// Smartschool users don't have real email addresses.
+///
+/// # Errors
+///
+/// Returns an error if a message or attachment can't be fetched or a file
+/// can't be written. Files written before the error occurred are kept.
+pub async fn export_eml<P: AsRef<Path>>(
+    client: &Client<'_>,
+    mailbox: MailBox,
+    dir: P,
+) -> Result<()> {
+    let dir = dir.as_ref();
+    let mut messages = stream(client, mailbox);
+    while let Some(message) = messages.try_next().await? {
+        let mut eml = Vec::new();
+        write_eml(client, &mut eml, message.id, "\r\n").await?;
+        let path = dir.join(format!("{}.eml", message.id));
+        file::write(&path, stream::once(async { Ok(Bytes::from(eml)) })).await?;
+    }
+    Ok(())
+}
+
+/// Exports every message in a mailbox, including attachments, to a single
+/// file in the MBOX format, which can be imported by regular mail clients.
+///
+/// See [`export_eml`](crate::messages::export_eml) for details about the
+/// exported messages.
+///
+/// # Errors
+///
+/// Returns an error if a message or attachment can't be fetched or the writer
+/// fails.
+pub async fn export_mbox<W: Write>(
+    client: &Client<'_>,
+    mailbox: MailBox,
+    mut writer: W,
+) -> Result<()> {
+    let mut messages = stream(client, mailbox);
+    while let Some(message) = messages.try_next().await? {
+        // Message bodies are always base64-encoded, so lines starting with
+        // `From ` can't occur and don't need to be escaped.
+        writeln!(
+            writer,
+            "From MAILER-DAEMON {}",
+            message.date.format("%a %b %e %H:%M:%S %Y")
+        )?;
+        write_eml(client, &mut writer, message.id, "\n").await?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Forwards a message.
+///
+/// The original message is quoted below the builder's body, the way the web
+/// interface does it, and the original attachments are carried forward. If
+/// the builder's subject is empty, the original subject prefixed with `Fwd: `
+/// is used.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The original message doesn't exist.
+/// * Any of the situations described in [`send`](crate::messages::send).
+pub async fn forward(
+    client: &Client<'_>,
+    original_id: MessageId,
+    mut message: MessageBuilder,
+) -> Result<()> {
+    let original = get_message(client, original_id).await?;
+    if message.subject.is_empty() {
+        message.subject = format!("Fwd: {}", original.subject);
+    }
+    message.body.push_str(&quote(&original));
+    message.forwarded_attachments = original.attachments.iter().map(|a| a.id).collect();
+    message.reference = Some(Reference::Forward(original_id));
+    send(client, message).await
+}
+
+/// Returns the user-created mailbox folders in arbitrary order.
+pub async fn get_folders(client: &Client<'_>) -> Result<Vec<MailFolder>> {
+    let url = format!("{}/messages/api/v1/folders", client.url());
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await
+}
+
+/// Returns the full contents of a message.
+///
+/// # Errors
+///
+/// Returns an error if the message doesn't exist.
+pub async fn get_message(client: &Client<'_>, id: MessageId) -> Result<MessageDetails> {
+    let url = format!("{}/messages/api/v1/messages/{}", client.url(), id);
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Message, id))?
+        .try_json()
+        .await
+}
+
+/// Returns a page of messages from a mailbox, sorted by date in descending
+/// order. Page numbers start at `1`; pages past the end of the mailbox are
+/// empty.
+pub async fn get_messages(
+    client: &Client<'_>,
+    mailbox: MailBox,
+    page: u32,
+) -> Result<Vec<Message>> {
+    let url = format!(
+        "{}/messages/api/v1/mailboxes/{}/messages?page={}",
+        client.url(),
+        mailbox,
+        page
+    );
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await
+}
+
+/// Converts an HTML message body to plain text.
+///
+/// Line breaks, paragraphs and list items are preserved as newlines, all other
+/// markup is stripped and common character references are decoded.
+pub fn html_to_text(html: &str) -> String {
+    // The patterns are constant, so compiling them can't fail.
+    static HIDDEN: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?is)<script.*?</script>|<style.*?</style>").unwrap());
+    static LINE_BREAKS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?i)<br\s*/?>|</(p|div|li|tr|h[1-6])\s*>").unwrap());
+    static LIST_ITEMS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?i)<li(\s[^>]*)?>").unwrap());
+    static TAGS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+    static BLANK_LINES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n{3,}").unwrap());
+
+    let text = HIDDEN.replace_all(html, "");
+    let text = text.replace(['\r', '\n'], " ");
+    let text = LINE_BREAKS.replace_all(&text, "\n");
+    let text = LIST_ITEMS.replace_all(&text, "- ");
+    let text = TAGS.replace_all(&text, "");
+    let text = decode_entities(&text);
+    let text = text.lines().map(str::trim).collect::<Vec<_>>().join("\n");
+    BLANK_LINES.replace_all(&text, "\n\n").trim().to_owned()
+}
+
+/// Marks a message as read.
+///
+/// # Errors
+///
+/// Returns an error if the message doesn't exist.
+pub async fn mark_read(client: &Client<'_>, id: MessageId) -> Result<()> {
+    let url = format!(
+        "{}/messages/api/v1/messages/{}/mark-as-read",
+        client.url(),
+        id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Message, id))?;
+    Ok(())
+}
+
+/// Marks a message as unread.
+///
+/// # Errors
+///
+/// Returns an error if the message doesn't exist.
+pub async fn mark_unread(client: &Client<'_>, id: MessageId) -> Result<()> {
+    let url = format!(
+        "{}/messages/api/v1/messages/{}/mark-as-unread",
+        client.url(),
+        id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Message, id))?;
+    Ok(())
+}
+
+/// Moves a message into the specified mailbox.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The message doesn't exist.
+/// * The destination folder doesn't exist.
+pub async fn move_message<I: Into<MailBox>>(
+    client: &Client<'_>,
+    id: MessageId,
+    destination: I,
+) -> Result<()> {
+    let mut form = HashMap::new();
+    form.insert("mailbox", Json::MailBox(destination.into()));
+
+    let url = format!("{}/messages/api/v1/messages/{}/move", client.url(), id);
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Message, id))?;
+    Ok(())
+}
+
+/// Moves a message to the [`Trash`](crate::messages::MailBox::Trash) mailbox.
+/// If you want to permanently delete the message instead, use
+/// [`delete_forever`](crate::messages::delete_forever).
+///
+/// # Errors
+///
+/// Returns an error if the message doesn't exist.
+pub async fn move_to_trash(client: &Client<'_>, id: MessageId) -> Result<()> {
+    let url = format!("{}/messages/api/v1/messages/{}/trash", client.url(), id);
+    client
+        .http_client()
+        .post(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Message, id))?;
+    Ok(())
+}
+
+/// Replies to a message.
+///
+/// The original message is quoted below the builder's body, the way the web
+/// interface does it, and the reply is linked to the original message's
+/// thread. If the builder doesn't have any recipients, the reply is addressed
+/// to the original sender. If the builder's subject is empty, the original
+/// subject prefixed with `Re: ` is used.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The original message doesn't exist.
+/// * Any of the situations described in [`send`](crate::messages::send).
+pub async fn reply(
+    client: &Client<'_>,
+    original_id: MessageId,
+    mut message: MessageBuilder,
+) -> Result<()> {
+    let original = get_message(client, original_id).await?;
+    if message.to.is_empty() && message.cc.is_empty() && message.bcc.is_empty() {
+        message.to.push(Recipient::User(original.sender.id.clone()));
+    }
+    if message.subject.is_empty() {
+        message.subject = format!("Re: {}", original.subject);
+    }
+    message.body.push_str(&quote(&original));
+    message.reference = Some(Reference::Reply(original_id));
+    send(client, message).await
+}
+
+/// Returns the messages matching a query, sorted by date in descending order.
+///
+/// # Errors
+///
+/// Returns an error if the query's date range ends before it starts.
+pub async fn search(client: &Client<'_>, query: &MessageQuery) -> Result<Vec<Message>> {
+    if let (Some(since), Some(until)) = (query.since, query.until) {
+        if since > until {
+            return Err(Error::Validation {
+                field: "until",
+                reason: "must not be earlier than `since`".to_owned(),
+            });
+        }
+    }
+
+    let url = format!("{}/messages/api/v1/search", client.url());
+    client
+        .http_client()
+        .post(&url)
+        .json(query)
+        .try_send()
+        .await?
+        .try_json()
+        .await
+}
+
+/// Searches the directory for recipients matching a query, like the
+/// autocomplete field when composing a message in the web interface.
+///
+/// The query is matched against names and descriptions. Results are sorted by
+/// relevance.
+pub async fn search_recipients(client: &Client<'_>, query: &str) -> Result<Vec<RecipientEntry>> {
+    let url = format!("{}/messages/api/v1/recipients", client.url());
+    client
+        .http_client()
+        .get(&url)
+        .query(&[("query", query)])
+        .try_send()
+        .await?
+        .try_json()
+        .await
+}
+
+/// Sends a message.
+///
+/// Attachments are uploaded to a fresh
+/// [`UploadDirectory`](crate::upload::UploadDirectory) before the message
+/// itself is sent.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The message doesn't have any recipients.
+/// * One of the recipients doesn't exist.
+/// * One of the attachments has an [illegal file
+///   name](crate::upload::upload_file).
+pub async fn send(client: &Client<'_>, message: MessageBuilder) -> Result<()> {
+    if message.to.is_empty() && message.cc.is_empty() && message.bcc.is_empty() {
+        return Err(Error::Validation {
+            field: "to",
+            reason: "must contain at least one recipient".to_owned(),
+        });
+    }
+
+    let upload_dir = if message.attachments.is_empty() {
+        None
+    } else {
+        let upload_dir = upload::get_upload_directory(client).await?;
+        for file in message.attachments {
+            upload::upload_file(client, upload_dir.clone(), file).await?;
+        }
+        Some(upload_dir)
+    };
+
+    let form = SendMessage {
+        bcc: &message.bcc,
+        body: &message.body,
+        cc: &message.cc,
+        forwarded_attachments: &message.forwarded_attachments,
+        priority: message.priority,
+        reference: message.reference,
+        subject: &message.subject,
+        to: &message.to,
+        upload_dir: upload_dir.as_ref().map(UploadDirectory::as_str),
+    };
+
+    let url = format!("{}/messages/api/v1/messages", client.url());
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await?;
+    Ok(())
+}
+
+/// Changes a message's flag. Use [`Flag::None`](crate::messages::Flag::None)
+/// to remove the flag.
+///
+/// # Errors
+///
+/// Returns an error if the message doesn't exist.
+pub async fn set_flag(client: &Client<'_>, id: MessageId, flag: Flag) -> Result<()> {
+    let mut form = HashMap::new();
+    form.insert("flag", Json::MessageFlag(flag));
+
+    let url = format!("{}/messages/api/v1/messages/{}/flag", client.url(), id);
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Message, id))?;
+    Ok(())
+}
+
+/// Returns a non-blocking stream of all messages in a mailbox, sorted by date
+/// in descending order.
+///
+/// Pages are fetched with [`get_messages`](crate::messages::get_messages) as
+/// the stream is polled. The stream ends after the first empty page or the
+/// first error.
+pub fn stream<'a>(client: &'a Client<'_>, mailbox: MailBox) -> BoxStream<'a, Result<Message>> {
+    stream::try_unfold(1, move |page| async move {
+        let messages = get_messages(client, mailbox, page).await?;
+        let next = if messages.is_empty() {
+            None
+        } else {
+            Some((stream::iter(messages.into_iter().map(Ok)), page + 1))
+        };
+        Ok::<_, Error>(next)
+    })
+    .try_flatten()
+    .boxed()
+}
+
+/// Returns a non-blocking stream of messages arriving in the inbox, polling
+/// the server at the specified interval.
+///
+/// Messages that are already in the inbox when the stream is first polled are
+/// skipped. New messages are yielded in the order they arrived and each
+/// message is yielded at most once. Errors are yielded as they occur, after
+/// which polling continues at the same interval, so the stream never ends by
+/// itself.
+pub fn watch_inbox<'a>(
+    client: &'a Client<'_>,
+    interval: Duration,
+) -> BoxStream<'a, Result<Message>> {
+    poll::every(
+        interval,
+        (None, true),
+        move |(mut newest, baseline)| async move {
+            match poll_inbox(client, &mut newest, baseline).await {
+                Ok(messages) => ((newest, false), Ok(messages)),
+                Err(err) => ((newest, baseline), Err(err)),
+            }
+        },
+    )
+}
+
+/// Decodes the character references commonly found in message bodies.
+fn decode_entities(text: &str) -> String {
+    // The pattern is constant, so compiling it can't fail.
+    static ENTITIES: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap());
+    ENTITIES
+        .replace_all(text, |captures: &regex::Captures<'_>| {
+            let entity = &captures[1];
+            let decoded = match entity {
+                "amp" => Some('&'),
+                "apos" => Some('\''),
+                "gt" => Some('>'),
+                "lt" => Some('<'),
+                "nbsp" => Some(' '),
+                "quot" => Some('"'),
+                _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                    u32::from_str_radix(&entity[2..], 16)
+                        .ok()
+                        .and_then(std::char::from_u32)
+                }
+                _ if entity.starts_with('#') => {
+                    entity[1..].parse().ok().and_then(std::char::from_u32)
+                }
+                _ => None,
+            };
+            decoded.map_or_else(|| captures[0].to_owned(), String::from)
+        })
+        .into_owned()
+}
+
+/// Encodes a header value as an RFC 2047 encoded word if it contains
+/// characters that aren't allowed in a header.
+fn encode_header(value: &str) -> String {
+    if is_plain_header(value) {
+        value.to_owned()
+    } else {
+        format!("=?UTF-8?B?{}?=", base64::encode(value))
+    }
+}
+
+/// Escapes the characters that have a special meaning in HTML.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns `true` if a header value can be written as is, even inside quotes.
+fn is_plain_header(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\')
+}
+
+/// Returns the inbox messages that are newer than the newest message seen
+/// before, oldest first, and updates the newest message seen.
+///
+/// Message identifiers increase as messages arrive, so older messages that
+/// end up on the first page again, for example after being restored, aren't
+/// returned. When establishing a baseline, only the first page is fetched and
+/// none of its messages are returned.
+async fn poll_inbox(
+    client: &Client<'_>,
+    newest: &mut Option<MessageId>,
+    baseline: bool,
+) -> Result<Vec<Message>> {
+    let last_seen = *newest;
+    let mut unseen = Vec::new();
+    let mut page = 1;
+    loop {
+        let messages = get_messages(client, MailBox::Inbox, page).await?;
+        if messages.is_empty() {
+            break;
+        }
+        let mut reached_seen = false;
+        for message in messages {
+            if last_seen.is_none_or(|last_seen| message.id > last_seen) {
+                unseen.push(message);
+            } else {
+                reached_seen = true;
+            }
+        }
+        if baseline || reached_seen {
+            break;
+        }
+        page += 1;
+    }
+
+    unseen.sort_by_key(|message| message.id);
+    if let Some(message) = unseen.last() {
+        *newest = Some(message.id);
+    }
+    if baseline {
+        unseen.clear();
+    }
+    Ok(unseen)
+}
+
+/// Quotes a message the way the web interface does when replying to or
+/// forwarding it.
+fn quote(original: &MessageDetails) -> String {
+    let names = |correspondents: &[Correspondent]| {
+        correspondents
+            .iter()
+            .map(|correspondent| escape_html(&correspondent.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!(
+        "<br><br><hr><b>Van:</b> {}<br><b>Verzonden:</b> {}<br><b>Aan:</b> {}<br>\
+         <b>Onderwerp:</b> {}<br><br><blockquote>{}</blockquote>",
+        escape_html(&original.sender.name),
+        original.date.format("%d/%m/%Y %H:%M"),
+        names(&original.to),
+        escape_html(&original.subject),
+        original.body
+    )
+}
+
+/// Writes base64-encoded data, wrapped at 76 characters per line.
+fn write_base64<W: Write>(mut writer: W, data: &[u8], eol: &str) -> io::Result<()> {
+    let encoded = base64::encode(data);
+    // Base64 output is always ASCII, so splitting it into chunks can't split a
+    // character.
+    for line in encoded.as_bytes().chunks(76) {
+        writer.write_all(line)?;
+        writer.write_all(eol.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Fetches a message and its attachments and writes them in the Internet
+/// Message Format, using the specified line ending.
+async fn write_eml<W: Write>(
+    client: &Client<'_>,
+    mut writer: W,
+    id: MessageId,
+    eol: &str,
+) -> Result<()> {
+    let message = get_message(client, id).await?;
+    let domain = Url::parse(client.url())
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+        .unwrap_or_else(|| "smartschool.invalid".to_owned());
+    let address = |correspondent: &Correspondent| {
+        // Plain display names are quoted, since they can contain commas.
+        // Encoded words aren't allowed inside quotes, but can't contain commas.
+        let name = &correspondent.name;
+        if is_plain_header(name) {
+            format!("\"{}\" <{}@{}>", name, correspondent.id, domain)
+        } else {
+            format!("{} <{}@{}>", encode_header(name), correspondent.id, domain)
+        }
+    };
+    let addresses = |correspondents: &[Correspondent]| {
+        correspondents
+            .iter()
+            .map(address)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let boundary = format!("smartschool-message-{}", message.id);
+
+    write!(writer, "From: {}{}", address(&message.sender), eol)?;
+    if !message.to.is_empty() {
+        write!(writer, "To: {}{}", addresses(&message.to), eol)?;
+    }
+    if !message.cc.is_empty() {
+        write!(writer, "Cc: {}{}", addresses(&message.cc), eol)?;
+    }
+    write!(
+        writer,
+        "Subject: {}{}",
+        encode_header(&message.subject),
+        eol
+    )?;
+    write!(writer, "Date: {}{}", message.date.to_rfc2822(), eol)?;
+    write!(writer, "Message-ID: <{}@{}>{}", message.id, domain, eol)?;
+    write!(writer, "MIME-Version: 1.0{}", eol)?;
+    write!(
+        writer,
+        "Content-Type: multipart/mixed; boundary=\"{}\"{}{}",
+        boundary, eol, eol
+    )?;
+
+    write!(writer, "--{}{}", boundary, eol)?;
+    write!(writer, "Content-Type: text/html; charset=utf-8{}", eol)?;
+    write!(writer, "Content-Transfer-Encoding: base64{}{}", eol, eol)?;
+    write_base64(&mut writer, message.body.as_bytes(), eol)?;
+
+    for attachment in &message.attachments {
+        let contents: Vec<u8> = download_attachment(client, message.id, attachment.id)
+            .await?
+            .try_fold(Vec::new(), |mut contents, chunk| async move {
+                contents.extend_from_slice(&chunk);
+                Ok(contents)
+            })
+            .await?;
+        let name = encode_header(&attachment.name);
+        write!(writer, "--{}{}", boundary, eol)?;
+        write!(
+            writer,
+            "Content-Type: {}; name=\"{}\"{}",
+            attachment.mime_type, name, eol
+        )?;
+        write!(
+            writer,
+            "Content-Disposition: attachment; filename=\"{}\"{}",
+            name, eol
+        )?;
+        write!(writer, "Content-Transfer-Encoding: base64{}{}", eol, eol)?;
+        write_base64(&mut writer, &contents, eol)?;
+    }
+
+    write!(writer, "--{}--{}", boundary, eol)?;
+    Ok(())
+}
+
+/// A file attached to a message.
+#[derive(Clone, Debug, Deserialize, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    /// The attachment's identifier.
+    pub id: AttachmentId,
+    /// The attachment's MIME type.
+    pub mime_type: String,
+    /// The attachment's file name.
+    pub name: String,
+    /// The attachment's size in bytes.
+    pub size: u64,
+}
+
+/// A handle to an [`Attachment`](crate::messages::Attachment).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct AttachmentId(u64);
+
+impl AttachmentId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for AttachmentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for AttachmentId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A builder to perform state changes on many messages at once, returned by
+/// [`bulk`](crate::messages::bulk).
+pub struct Bulk<'a, 'b> {
+    client: &'a Client<'b>,
+    concurrency: usize,
+    operations: Vec<(MessageId, Operation)>,
+}
+
+impl<'a, 'b> Bulk<'a, 'b> {
+    /// Sets the maximum number of messages that are processed concurrently.
+    /// Defaults to `4`.
+    pub fn concurrency(mut self, concurrency: usize) -> Bulk<'a, 'b> {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Permanently deletes the messages. See
+    /// [`delete_forever`](crate::messages::delete_forever).
+    pub fn delete_forever<I>(self, ids: I) -> Bulk<'a, 'b>
+    where
+        I: IntoIterator<Item = MessageId>,
+    {
+        self.push(ids, Operation::DeleteForever)
+    }
+
+    /// Marks the messages as read. See
+    /// [`mark_read`](crate::messages::mark_read).
+    pub fn mark_read<I>(self, ids: I) -> Bulk<'a, 'b>
+    where
+        I: IntoIterator<Item = MessageId>,
+    {
+        self.push(ids, Operation::MarkRead)
+    }
+
+    /// Marks the messages as unread. See
+    /// [`mark_unread`](crate::messages::mark_unread).
+    pub fn mark_unread<I>(self, ids: I) -> Bulk<'a, 'b>
+    where
+        I: IntoIterator<Item = MessageId>,
+    {
+        self.push(ids, Operation::MarkUnread)
+    }
+
+    /// Moves the messages into the specified mailbox. See
+    /// [`move_message`](crate::messages::move_message).
+    pub fn move_to<I, M>(self, ids: I, destination: M) -> Bulk<'a, 'b>
+    where
+        I: IntoIterator<Item = MessageId>,
+        M: Into<MailBox>,
+    {
+        self.push(ids, Operation::Move(destination.into()))
+    }
+
+    /// Moves the messages to the [`Trash`](crate::messages::MailBox::Trash)
+    /// mailbox. See [`move_to_trash`](crate::messages::move_to_trash).
+    pub fn move_to_trash<I>(self, ids: I) -> Bulk<'a, 'b>
+    where
+        I: IntoIterator<Item = MessageId>,
+    {
+        self.push(ids, Operation::MoveToTrash)
+    }
+
+    /// Changes the messages' flag. See [`set_flag`](crate::messages::set_flag).
+    pub fn set_flag<I>(self, ids: I, flag: Flag) -> Bulk<'a, 'b>
+    where
+        I: IntoIterator<Item = MessageId>,
+    {
+        self.push(ids, Operation::SetFlag(flag))
+    }
+
+    /// Performs the queued operations and returns the outcome for every
+    /// affected message, in arbitrary order.
+    ///
+    /// Operations on the same message are performed in the order they were
+    /// queued. The first failing operation on a message determines its outcome
+    /// and prevents the remaining operations on that message from being
+    /// performed. Failures don't affect other messages.
+    pub async fn execute(self) -> Vec<(MessageId, Result<()>)> {
+        let client = self.client;
+
+        let mut indices = HashMap::new();
+        let mut grouped: Vec<(MessageId, Vec<Operation>)> = Vec::new();
+        for (id, operation) in self.operations {
+            let index = *indices.entry(id).or_insert_with(|| {
+                grouped.push((id, Vec::new()));
+                grouped.len() - 1
+            });
+            grouped[index].1.push(operation);
+        }
+
+        stream::iter(grouped)
+            .map(|(id, operations)| async move {
+                for operation in operations {
+                    let result = match operation {
+                        Operation::DeleteForever => delete_forever(client, id).await,
+                        Operation::MarkRead => mark_read(client, id).await,
+                        Operation::MarkUnread => mark_unread(client, id).await,
+                        Operation::Move(destination) => move_message(client, id, destination).await,
+                        Operation::MoveToTrash => move_to_trash(client, id).await,
+                        Operation::SetFlag(flag) => set_flag(client, id, flag).await,
+                    };
+                    if result.is_err() {
+                        return (id, result);
+                    }
+                }
+                (id, Ok(()))
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    fn push<I>(mut self, ids: I, operation: Operation) -> Bulk<'a, 'b>
+    where
+        I: IntoIterator<Item = MessageId>,
+    {
+        self.operations
+            .extend(ids.into_iter().map(|id| (id, operation)));
+        self
+    }
+}
+
+/// A user who sent or received a message.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Correspondent {
+    /// The user's identifier.
+    pub id: UserId,
+    /// The user's name.
+    pub name: String,
+}
+
+/// A colored flag used to categorize messages.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Flag {
+    /// No flag.
+    #[default]
+    None,
+    /// A blue flag.
+    Blue,
+    /// A green flag.
+    Green,
+    /// An orange flag.
+    Orange,
+    /// A red flag.
+    Red,
+    /// A yellow flag.
+    Yellow,
+}
+
+/// A mailbox containing messages.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MailBox {
+    /// A user-created mailbox folder.
+    Custom(MailFolderId),
+    /// The mailbox containing received messages.
+    Inbox,
+    /// The mailbox containing sent messages.
+    ///
+    /// Smartschool internally refers to this mailbox as `outbox`.
+    Sent,
+    /// The mailbox containing trashed messages.
+    Trash,
+}
+
+impl fmt::Display for MailBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MailBox::Custom(id) => fmt::Display::fmt(id, f),
+            MailBox::Inbox => write!(f, "inbox"),
+            MailBox::Sent => write!(f, "outbox"),
+            MailBox::Trash => write!(f, "trash"),
+        }
+    }
+}
+
+impl From<MailFolderId> for MailBox {
+    fn from(id: MailFolderId) -> Self {
+        MailBox::Custom(id)
+    }
+}
+
+/// A user-created mailbox folder.
+#[derive(Clone, Debug, Deserialize, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MailFolder {
+    /// The folder's identifier.
+    pub id: MailFolderId,
+    /// The folder's name.
+    pub name: String,
+    /// The identifier of the folder's parent folder, if it is nested inside
+    /// another folder.
+    pub parent_id: Option<MailFolderId>,
+    /// The number of unread messages in the folder.
+    pub unread_count: u32,
+}
+
+/// A handle to a [`MailFolder`](crate::messages::MailFolder).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct MailFolderId(u64);
+
+impl MailFolderId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for MailFolderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for MailFolderId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A summary of a message, as listed in a mailbox.
+#[derive(Clone, Debug, Deserialize, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    /// The date when the message was sent.
+    pub date: DateTime<FixedOffset>,
+    /// The message's flag.
+    #[serde(default)]
+    pub flag: Flag,
+    /// `true` if the message has one or more attachments.
+    pub has_attachments: bool,
+    /// The message's identifier.
+    pub id: MessageId,
+    /// `true` if the message has been read.
+    pub is_read: bool,
+    /// The name of the message's sender.
+    #[serde(rename = "from")]
+    pub sender: String,
+    /// The message's subject.
+    pub subject: String,
+}
+
+/// A builder to construct a message that can be sent with
+/// [`send`](crate::messages::send).
+pub struct MessageBuilder {
+    attachments: Vec<File>,
+    bcc: Vec<Recipient>,
+    body: String,
+    cc: Vec<Recipient>,
+    forwarded_attachments: Vec<AttachmentId>,
+    priority: Priority,
+    reference: Option<Reference>,
+    subject: String,
+    to: Vec<Recipient>,
+}
+
+impl MessageBuilder {
+    /// Creates a builder for a message with the specified subject, an empty
+    /// body, no recipients and [normal](crate::messages::Priority::Normal)
+    /// priority.
+    pub fn new<T: Into<String>>(subject: T) -> MessageBuilder {
+        MessageBuilder {
+            attachments: Vec::new(),
+            bcc: Vec::new(),
+            body: String::new(),
+            cc: Vec::new(),
+            forwarded_attachments: Vec::new(),
+            priority: Priority::default(),
+            reference: None,
+            subject: subject.into(),
+            to: Vec::new(),
+        }
+    }
+
+    /// Adds an attachment, which will be uploaded when the message is sent.
+    pub fn attach(mut self, file: File) -> MessageBuilder {
+        self.attachments.push(file);
+        self
+    }
+
+    /// Adds a blind carbon copy recipient.
+    pub fn bcc<R: Into<Recipient>>(mut self, recipient: R) -> MessageBuilder {
+        self.bcc.push(recipient.into());
+        self
+    }
+
+    /// Sets the message's body, formatted as HTML.
+    pub fn body<T: Into<String>>(mut self, body: T) -> MessageBuilder {
+        self.body = body.into();
+        self
+    }
+
+    /// Adds a carbon copy recipient.
+    pub fn cc<R: Into<Recipient>>(mut self, recipient: R) -> MessageBuilder {
+        self.cc.push(recipient.into());
+        self
+    }
+
+    /// Sets the message's priority.
+    pub fn priority(mut self, priority: Priority) -> MessageBuilder {
+        self.priority = priority;
+        self
+    }
+
+    /// Adds a primary recipient.
+    pub fn to<R: Into<Recipient>>(mut self, recipient: R) -> MessageBuilder {
+        self.to.push(recipient.into());
+        self
+    }
+}
+
+/// The full contents of a message.
+#[derive(Clone, Debug, Deserialize, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageDetails {
+    /// The files attached to the message.
+    pub attachments: Vec<Attachment>,
+    /// The message's body, formatted as HTML.
+    pub body: String,
+    /// The message's carbon copy recipients.
+    pub cc: Vec<Correspondent>,
+    /// The date when the message was sent.
+    pub date: DateTime<FixedOffset>,
+    /// The message's identifier.
+    pub id: MessageId,
+    /// `true` if the message has been read.
+    pub is_read: bool,
+    /// The message's sender.
+    #[serde(rename = "from")]
+    pub sender: Correspondent,
+    /// The message's subject.
+    pub subject: String,
+    /// The message's primary recipients.
+    pub to: Vec<Correspondent>,
+}
+
+impl MessageDetails {
+    /// Returns the message's body converted to plain text.
+    ///
+    /// See [`html_to_text`](crate::messages::html_to_text) for details.
+    pub fn body_text(&self) -> String {
+        html_to_text(&self.body)
+    }
+}
+
+/// A handle to a [`Message`](crate::messages::Message).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct MessageId(u64);
+
+impl MessageId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for MessageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for MessageId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A query used to [`search`](crate::messages::search) for messages.
+///
+/// Filters are combined, so a message has to match all of them to be
+/// returned. An empty query matches every message in every mailbox.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    has_attachments: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mailbox: Option<MailBox>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sender: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    since: Option<DateTime<FixedOffset>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    unread_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    until: Option<DateTime<FixedOffset>>,
+}
+
+impl MessageQuery {
+    /// Creates an empty query.
+    pub fn new() -> MessageQuery {
+        MessageQuery::default()
+    }
+
+    /// Only matches messages with (`true`) or without (`false`) attachments.
+    pub fn has_attachments(mut self, has_attachments: bool) -> MessageQuery {
+        self.has_attachments = Some(has_attachments);
+        self
+    }
+
+    /// Only matches messages in the specified mailbox.
+    pub fn mailbox<I: Into<MailBox>>(mut self, mailbox: I) -> MessageQuery {
+        self.mailbox = Some(mailbox.into());
+        self
+    }
+
+    /// Only matches messages whose sender's name contains the specified
+    /// string.
+    pub fn sender<T: Into<String>>(mut self, sender: T) -> MessageQuery {
+        self.sender = Some(sender.into());
+        self
+    }
+
+    /// Only matches messages sent at or after the specified date.
+    pub fn since(mut self, date: DateTime<FixedOffset>) -> MessageQuery {
+        self.since = Some(date);
+        self
+    }
+
+    /// Only matches messages whose subject or body contains the specified
+    /// string.
+    pub fn text<T: Into<String>>(mut self, text: T) -> MessageQuery {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Only matches unread messages.
+    pub fn unread_only(mut self) -> MessageQuery {
+        self.unread_only = true;
+        self
+    }
+
+    /// Only matches messages sent at or before the specified date.
+    pub fn until(mut self, date: DateTime<FixedOffset>) -> MessageQuery {
+        self.until = Some(date);
+        self
+    }
+}
+
+/// A state change queued in a [`Bulk`](crate::messages::Bulk).
+#[derive(Clone, Copy)]
+enum Operation {
+    DeleteForever,
+    MarkRead,
+    MarkUnread,
+    Move(MailBox),
+    MoveToTrash,
+    SetFlag(Flag),
+}
+
+/// The priority of a message.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Priority {
+    /// A low-priority message.
+    Low,
+    /// A message with normal priority.
+    #[default]
+    Normal,
+    /// A high-priority message, highlighted in the recipient's mailbox.
+    High,
+}
+
+/// A recipient of a message.
+///
+/// Messages addressed to a class, a group or a meta-recipient are delivered to
+/// every user they comprise at the time of sending.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "type", content = "id", rename_all = "camelCase")]
+pub enum Recipient {
+    /// Every student in a class.
+    Class(ClassId),
+    /// Every member of a group.
+    Group(GroupId),
+    /// The parents or guardians of a student.
+    ParentsOf(UserId),
+    /// Every teacher of a student.
+    TeachersOf(UserId),
+    /// A single user.
+    User(UserId),
+}
+
+impl From<ClassId> for Recipient {
+    fn from(id: ClassId) -> Self {
+        Recipient::Class(id)
+    }
+}
+
+impl From<GroupId> for Recipient {
+    fn from(id: GroupId) -> Self {
+        Recipient::Group(id)
+    }
+}
+
+impl From<UserId> for Recipient {
+    fn from(id: UserId) -> Self {
+        Recipient::User(id)
+    }
+}
+
+/// A directory entry returned by
+/// [`search_recipients`](crate::messages::search_recipients).
+#[derive(Clone, Debug, Deserialize, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecipientEntry {
+    /// Additional information to tell entries with the same name apart, like a
+    /// student's class.
+    #[serde(default)]
+    pub description: String,
+    /// The entry's identifier.
+    pub id: String,
+    /// The kind of the entry.
+    #[serde(rename = "type")]
+    pub kind: RecipientKind,
+    /// The entry's name.
+    pub name: String,
+}
+
+impl RecipientEntry {
+    /// Returns the [`Recipient`](crate::messages::Recipient) that can be used to
+    /// address this entry, or `None` if the entry can't be addressed directly.
+    pub fn recipient(&self) -> Option<Recipient> {
+        let id = self.id.as_str();
+        match self.kind {
+            RecipientKind::Class => Some(Recipient::Class(ClassId::from(id))),
+            RecipientKind::Group => Some(Recipient::Group(GroupId::from(id))),
+            RecipientKind::Parent | RecipientKind::Student | RecipientKind::Teacher => {
+                Some(Recipient::User(UserId::from(id)))
+            }
+            RecipientKind::Other => None,
+        }
+    }
+}
+
+/// The kind of a [`RecipientEntry`](crate::messages::RecipientEntry).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecipientKind {
+    /// A class of students.
+    Class,
+    /// A group of users, like a course group or a staff group.
+    Group,
+    /// A parent or guardian of a student.
+    Parent,
+    /// A student.
+    Student,
+    /// A teacher or another staff member.
+    Teacher,
+    /// A kind of entry that isn't known to this crate.
+    #[serde(other)]
+    Other,
+}
+
+/// The message a sent message replies to or forwards.
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Reference {
+    Forward(MessageId),
+    #[serde(rename = "replyTo")]
+    Reply(MessageId),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SendMessage<'a> {
+    bcc: &'a [Recipient],
+    body: &'a str,
+    cc: &'a [Recipient],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    forwarded_attachments: &'a [AttachmentId],
+    priority: Priority,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    reference: Option<Reference>,
+    subject: &'a str,
+    to: &'a [Recipient],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upload_dir: Option<&'a str>,
+}
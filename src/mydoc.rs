@@ -1,9 +1,15 @@
 //! A virtual file system hosted on the server.
 
-use crate::{error::Result, http::TrySend, serde::Json, upload::UploadDirectory, Client};
+use crate::{
+    error::{EntityKind, Error, Result},
+    http::{TryJson, TrySend},
+    serde::Json,
+    upload::UploadDirectory,
+    Client,
+};
 use bytes::Bytes;
 use chrono::{DateTime, FixedOffset};
-use futures::{Stream, TryFutureExt, TryStreamExt};
+use futures::{Stream, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt};
 use uuid::Uuid;
@@ -27,9 +33,9 @@ pub async fn change_folder_color(
         .post(&url)
         .json(&form)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Folder, id))?
+        .try_json()
         .await
 }
 
@@ -59,9 +65,9 @@ pub async fn copy_file<I: Into<FolderId>>(
         .post(&url)
         .json(&form)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_identified_entity(EntityKind::File, source))?
+        .try_json()
         .await
 }
 
@@ -92,9 +98,9 @@ pub async fn copy_folder<I: Into<FolderId>>(
         .post(&url)
         .json(&form)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_identified_entity(EntityKind::Folder, source))?
+        .try_json()
         .await
 }
 
@@ -132,8 +138,7 @@ pub async fn create_file_from_template<I: Into<FolderId>>(
         .json(&form)
         .try_send()
         .await?
-        .json()
-        .err_into()
+        .try_json()
         .await
 }
 
@@ -155,6 +160,8 @@ pub async fn create_folder<I: Into<FolderId>>(
     name: &str,
     color: FolderColor,
 ) -> Result<Folder> {
+    validate_name("name", name)?;
+
     let mut form = HashMap::new();
     form.insert("color", Json::FolderColor(color));
     form.insert("name", Json::Str(name));
@@ -167,8 +174,7 @@ pub async fn create_folder<I: Into<FolderId>>(
         .json(&form)
         .try_send()
         .await?
-        .json()
-        .err_into()
+        .try_json()
         .await
 }
 
@@ -180,7 +186,12 @@ pub async fn create_folder<I: Into<FolderId>>(
 /// Returns an error if the file doesn't exist.
 pub async fn delete_file(client: &Client<'_>, id: FileId) -> Result<()> {
     let url = format!("{}/mydoc/api/v1/files/{}", client.url(), id);
-    client.http_client().delete(&url).try_send().await?;
+    client
+        .http_client()
+        .delete(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::File, id))?;
     Ok(())
 }
 
@@ -192,7 +203,12 @@ pub async fn delete_file(client: &Client<'_>, id: FileId) -> Result<()> {
 /// Returns an error if the folder doesn't exist.
 pub async fn delete_folder(client: &Client<'_>, id: CustomFolderId) -> Result<()> {
     let url = format!("{}/mydoc/api/v1/folders/{}", client.url(), id);
-    client.http_client().delete(&url).try_send().await?;
+    client
+        .http_client()
+        .delete(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Folder, id))?;
     Ok(())
 }
 
@@ -207,7 +223,12 @@ pub async fn download_file(
     id: FileId,
 ) -> Result<impl Stream<Item = Result<Bytes>>> {
     let url = format!("{}/mydoc/api/v1/files/{}/download", client.url(), id);
-    let response = client.http_client().get(&url).try_send().await?;
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::File, id))?;
     Ok(response.bytes_stream().err_into())
 }
 
@@ -231,7 +252,12 @@ pub async fn download_revision(
         file_id,
         revision_id
     );
-    let response = client.http_client().get(&url).try_send().await?;
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Revision, revision_id))?;
     Ok(response.bytes_stream().err_into())
 }
 
@@ -245,8 +271,7 @@ pub async fn get_file_history(client: &Client<'_>, id: FileId) -> Result<Vec<His
         .get(&url)
         .try_send()
         .await?
-        .json()
-        .err_into()
+        .try_json()
         .await
 }
 
@@ -259,8 +284,7 @@ pub async fn get_file_revisions(client: &Client<'_>, id: FileId) -> Result<Vec<R
         .get(&url)
         .try_send()
         .await?
-        .json()
-        .err_into()
+        .try_json()
         .await
 }
 
@@ -283,8 +307,9 @@ pub async fn get_folder_contents<I: Into<FolderId>>(
         .http_client()
         .get(&url)
         .try_send()
-        .await?
-        .json()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Folder, id))?
+        .try_json()
         .await?;
     Ok((response.files, response.folders))
 }
@@ -302,8 +327,7 @@ pub async fn get_folder_history(
         .get(&url)
         .try_send()
         .await?
-        .json()
-        .err_into()
+        .try_json()
         .await
 }
 
@@ -322,9 +346,9 @@ pub async fn get_folder_parents(
         .http_client()
         .get(&url)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Folder, id))?
+        .try_json()
         .await
 }
 
@@ -337,8 +361,7 @@ pub async fn get_recent_files(client: &Client<'_>) -> Result<Vec<File>> {
         .get(&url)
         .try_send()
         .await?
-        .json()
-        .err_into()
+        .try_json()
         .await
 }
 
@@ -357,9 +380,9 @@ pub async fn mark_file_as_favorite(client: &Client<'_>, id: FileId) -> Result<Fi
         .http_client()
         .post(&url)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::File, id))?
+        .try_json()
         .await
 }
 
@@ -378,9 +401,9 @@ pub async fn mark_folder_as_favorite(client: &Client<'_>, id: CustomFolderId) ->
         .http_client()
         .post(&url)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Folder, id))?
+        .try_json()
         .await
 }
 
@@ -411,9 +434,9 @@ pub async fn move_file<I: Into<FolderId>>(
         .post(&url)
         .json(&form)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_identified_entity(EntityKind::File, source))?
+        .try_json()
         .await
 }
 
@@ -445,9 +468,9 @@ pub async fn move_folder<I: Into<FolderId>>(
         .post(&url)
         .json(&form)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_identified_entity(EntityKind::Folder, source))?
+        .try_json()
         .await
 }
 
@@ -461,7 +484,12 @@ pub async fn move_folder<I: Into<FolderId>>(
 /// * The new name contains `/`, `:`, `*`, `?`, `"`, `\\`, `<`, `>` or `|`.
 /// * The new name starts or ends with a `.`.
 /// * The new name is the same as the current name.
+///
+/// Illegal names are detected before sending the request and produce an
+/// [`Error::Validation`](crate::Error::Validation).
 pub async fn rename_file(client: &Client<'_>, id: FileId, new_name: &str) -> Result<File> {
+    validate_name("new_name", new_name)?;
+
     let mut form = HashMap::new();
     form.insert("newName", Json::Str(new_name));
 
@@ -471,9 +499,9 @@ pub async fn rename_file(client: &Client<'_>, id: FileId, new_name: &str) -> Res
         .post(&url)
         .json(&form)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::File, id))?
+        .try_json()
         .await
 }
 
@@ -492,6 +520,8 @@ pub async fn rename_folder(
     id: CustomFolderId,
     new_name: &str,
 ) -> Result<Folder> {
+    validate_name("new_name", new_name)?;
+
     let mut form = HashMap::new();
     form.insert("newName", Json::Str(new_name));
 
@@ -501,9 +531,9 @@ pub async fn rename_folder(
         .post(&url)
         .json(&form)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Folder, id))?
+        .try_json()
         .await
 }
 
@@ -537,9 +567,9 @@ pub async fn restore_file<I: Into<FolderId>>(
         .post(&url)
         .json(&form)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_identified_entity(EntityKind::File, id))?
+        .try_json()
         .await
 }
 
@@ -571,9 +601,9 @@ pub async fn restore_folder<I: Into<FolderId>>(
         .post(&url)
         .json(&form)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_identified_entity(EntityKind::Folder, id))?
+        .try_json()
         .await
 }
 
@@ -603,9 +633,9 @@ pub async fn restore_revision(
         .http_client()
         .post(&url)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Revision, revision_id))?
+        .try_json()
         .await
 }
 
@@ -618,7 +648,12 @@ pub async fn restore_revision(
 /// Returns an error if the file doesn't exist.
 pub async fn trash_file(client: &Client<'_>, id: FileId) -> Result<()> {
     let url = format!("{}/mydoc/api/v1/files/{}/trash", client.url(), id);
-    client.http_client().post(&url).try_send().await?;
+    client
+        .http_client()
+        .post(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::File, id))?;
     Ok(())
 }
 
@@ -631,7 +666,12 @@ pub async fn trash_file(client: &Client<'_>, id: FileId) -> Result<()> {
 /// Returns an error if the folder doesn't exist.
 pub async fn trash_folder(client: &Client<'_>, id: CustomFolderId) -> Result<()> {
     let url = format!("{}/mydoc/api/v1/folders/{}/trash", client.url(), id);
-    client.http_client().post(&url).try_send().await?;
+    client
+        .http_client()
+        .post(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Folder, id))?;
     Ok(())
 }
 
@@ -650,9 +690,9 @@ pub async fn unmark_file_as_favorite(client: &Client<'_>, id: FileId) -> Result<
         .http_client()
         .post(&url)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::File, id))?
+        .try_json()
         .await
 }
 
@@ -671,9 +711,9 @@ pub async fn unmark_folder_as_favorite(client: &Client<'_>, id: CustomFolderId)
         .http_client()
         .post(&url)
         .try_send()
-        .await?
-        .json()
-        .err_into()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Folder, id))?
+        .try_json()
         .await
 }
 
@@ -709,13 +749,30 @@ pub async fn upload<I: Into<FolderId>>(
         .json(&form)
         .try_send()
         .await?
-        .json()
+        .try_json()
         .await?;
 
     // The `files` field of the response is actually a map where the key is the
     // file's identifier and the value is the file itself. Since the files
     // themselves have an `id` field anyway, we discard the keys.
-    Ok(response.files.into_iter().map(|(_, value)| value).collect())
+    Ok(response.files.into_values().collect())
+}
+
+/// Checks a file or folder name against the rules enforced by the server, so
+/// illegal names are rejected without a round trip.
+pub(crate) fn validate_name(field: &'static str, name: &str) -> Result<()> {
+    const ILLEGAL_CHARS: &[char] = &['/', ':', '*', '?', '"', '\\', '<', '>', '|'];
+
+    let reason = if name.is_empty() {
+        "must not be empty".to_owned()
+    } else if let Some(c) = name.chars().find(|c| ILLEGAL_CHARS.contains(c)) {
+        format!("must not contain `{}`", c)
+    } else if name.starts_with('.') || name.ends_with('.') {
+        "must not start or end with a `.`".to_owned()
+    } else {
+        return Ok(());
+    };
+    Err(Error::Validation { field, reason })
 }
 
 /// A handle to a [`Folder`](crate::mydoc::Folder).
@@ -840,7 +897,7 @@ pub struct Folder {
 }
 
 /// The color of a folder.
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum FolderColor {
     /// An aqua-colored folder.
@@ -864,15 +921,10 @@ pub enum FolderColor {
     /// A white-colored folder.
     White,
     /// A yellow-colored folder.
+    #[default]
     Yellow,
 }
 
-impl Default for FolderColor {
-    fn default() -> Self {
-        FolderColor::Yellow
-    }
-}
-
 /// An identifier of a folder in the virtual file system.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum FolderId {
@@ -0,0 +1,184 @@
+//! The school's front-page news feed.
+
+use crate::{
+    error::{EntityKind, Result},
+    http::{TryJson, TrySend},
+    user::{ClassId, UserId},
+    Client,
+};
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use futures::{Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Downloads an attachment of a news item and returns its contents as a
+/// non-blocking stream of [`Bytes`](bytes::Bytes).
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The news item doesn't exist.
+/// * The attachment doesn't exist or isn't associated with the news item.
+pub async fn download_attachment(
+    client: &Client<'_>,
+    item_id: NewsItemId,
+    attachment_id: NewsAttachmentId,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let url = format!(
+        "{}/news/api/v1/items/{}/attachments/{}/download",
+        client.url(),
+        item_id,
+        attachment_id
+    );
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_identified_entity(EntityKind::NewsItem, item_id))?;
+    Ok(response.bytes_stream().err_into())
+}
+
+/// Returns the users whose birthday is today or later this week, sorted by
+/// date in ascending order.
+pub async fn get_birthdays(client: &Client<'_>) -> Result<Vec<Birthday>> {
+    let url = format!("{}/news/api/v1/birthdays", client.url());
+    let mut birthdays: Vec<Birthday> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    birthdays.sort_by(|a, b| (a.date, &a.name).cmp(&(b.date, &b.name)));
+    Ok(birthdays)
+}
+
+/// Returns a news item.
+///
+/// # Errors
+///
+/// Returns an error if the news item doesn't exist.
+pub async fn get_item(client: &Client<'_>, id: NewsItemId) -> Result<NewsItem> {
+    let url = format!("{}/news/api/v1/items/{}", client.url(), id);
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::NewsItem, id))?
+        .try_json()
+        .await
+}
+
+/// Returns a page of news items, sorted by publication date in descending
+/// order. Pages start at `1` and an empty page indicates the end of the feed.
+pub async fn get_items(client: &Client<'_>, page: u32) -> Result<Vec<NewsItem>> {
+    let url = format!("{}/news/api/v1/items", client.url());
+    client
+        .http_client()
+        .get(&url)
+        .query(&[("page", page)])
+        .try_send()
+        .await?
+        .try_json()
+        .await
+}
+
+/// A user's birthday.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Birthday {
+    /// The user's class, or `None` if the user isn't a student.
+    pub class: Option<ClassId>,
+    /// The date of the birthday in the current year.
+    pub date: NaiveDate,
+    /// The user's identifier.
+    pub id: UserId,
+    /// The user's full name.
+    pub name: String,
+}
+
+/// An attachment of a [`NewsItem`](crate::news::NewsItem).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewsAttachment {
+    /// The attachment's identifier.
+    pub id: NewsAttachmentId,
+    /// The attachment's MIME type.
+    pub mime_type: String,
+    /// The attachment's file name.
+    pub name: String,
+    /// The attachment's size in bytes.
+    pub size: u64,
+}
+
+/// A handle to a [`NewsAttachment`](crate::news::NewsAttachment).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct NewsAttachmentId(u64);
+
+impl NewsAttachmentId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for NewsAttachmentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for NewsAttachmentId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A news item on the school's front page.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewsItem {
+    /// The files attached to the news item.
+    #[serde(default)]
+    pub attachments: Vec<NewsAttachment>,
+    /// The name of the news item's author.
+    pub author: String,
+    /// The news item's body, formatted as HTML.
+    pub body: String,
+    /// The news item's identifier.
+    pub id: NewsItemId,
+    /// The date when the news item is published.
+    pub published_from: DateTime<FixedOffset>,
+    /// The date when the news item is taken down, or `None` if it stays
+    /// published indefinitely.
+    pub published_until: Option<DateTime<FixedOffset>>,
+    /// The news item's title.
+    pub title: String,
+}
+
+/// A handle to a [`NewsItem`](crate::news::NewsItem).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct NewsItemId(u64);
+
+impl NewsItemId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for NewsItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for NewsItemId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
@@ -0,0 +1,230 @@
+//! The notification feed behind the bell icon.
+
+use crate::{
+    agenda::LessonId,
+    courses::{CourseId, DocumentId},
+    error::{EntityKind, Result},
+    http::{TryJson, TrySend},
+    messages::MessageId,
+    results::EvaluationId,
+    Client,
+};
+use chrono::{DateTime, FixedOffset};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use futures_timer::Delay;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Reverse, fmt, time::Duration};
+
+/// Deletes a notification from the feed.
+///
+/// # Errors
+///
+/// Returns an error if the notification doesn't exist.
+pub async fn delete(client: &Client<'_>, id: NotificationId) -> Result<()> {
+    let url = format!("{}/notifications/api/v1/notifications/{}", client.url(), id);
+    client
+        .http_client()
+        .delete(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Notification, id))?;
+    Ok(())
+}
+
+/// Returns the notifications of the logged-in user, sorted by date in
+/// descending order.
+pub async fn get(client: &Client<'_>) -> Result<Vec<Notification>> {
+    let url = format!("{}/notifications/api/v1/notifications", client.url());
+    let mut notifications: Vec<Notification> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    notifications.sort_by_key(|notification| Reverse(notification.date));
+    Ok(notifications)
+}
+
+/// Marks every notification as read, which clears the badge count.
+pub async fn mark_all_read(client: &Client<'_>) -> Result<()> {
+    let url = format!("{}/notifications/api/v1/notifications/read", client.url());
+    client.http_client().post(&url).try_send().await?;
+    Ok(())
+}
+
+/// Marks a notification as read.
+///
+/// # Errors
+///
+/// Returns an error if the notification doesn't exist.
+pub async fn mark_read(client: &Client<'_>, id: NotificationId) -> Result<()> {
+    let url = format!(
+        "{}/notifications/api/v1/notifications/{}/read",
+        client.url(),
+        id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Notification, id))?;
+    Ok(())
+}
+
+/// Returns a non-blocking stream of notifications as they arrive, using the
+/// long-polling channel of the web interface.
+///
+/// Every poll is held open by the server until a notification arrives or the
+/// poll times out, so notifications are yielded within seconds. Notifications
+/// that already exist when the stream is first polled are skipped. Errors are
+/// yielded as they occur, after which polling resumes after a short delay, so
+/// the stream never ends by itself.
+pub fn subscribe<'a>(client: &'a Client<'_>) -> BoxStream<'a, Result<Notification>> {
+    const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+    stream::unfold((None, false), move |(cursor, failed)| async move {
+        if failed {
+            Delay::new(RETRY_DELAY).await;
+        }
+        let (notifications, state) = match poll(client, cursor.as_deref()).await {
+            Ok(poll) => {
+                let notifications = if cursor.is_some() {
+                    poll.notifications.into_iter().map(Ok).collect()
+                } else {
+                    Vec::new()
+                };
+                (notifications, (Some(poll.cursor), false))
+            }
+            Err(err) => (vec![Err(err)], (cursor, true)),
+        };
+        Some((stream::iter(notifications), state))
+    })
+    .flatten()
+    .boxed()
+}
+
+/// Waits for notifications newer than the cursor and returns them, along with
+/// the cursor for the next poll. Without a cursor, the server immediately
+/// returns the current cursor.
+async fn poll(client: &Client<'_>, cursor: Option<&str>) -> Result<Poll> {
+    // The server holds a poll open for at most 30 seconds.
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    let url = format!("{}/notifications/api/v1/poll", client.url());
+    let mut request = client.http_client().get(&url).timeout(TIMEOUT);
+    if let Some(cursor) = cursor {
+        request = request.query(&[("cursor", cursor)]);
+    }
+    request.try_send().await?.try_json().await
+}
+
+/// A notification.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    /// The date when the notification was created.
+    pub date: DateTime<FixedOffset>,
+    /// The notification's description, or an empty string if there is none.
+    #[serde(default)]
+    pub description: String,
+    /// The notification's identifier.
+    pub id: NotificationId,
+    /// `true` if the notification has been read.
+    pub is_read: bool,
+    /// The kind of event the notification is about, including the identifiers
+    /// of the items involved.
+    #[serde(default, rename = "payload")]
+    pub kind: NotificationKind,
+    /// The name of the module the notification originates from, e.g.
+    /// `Messages` or `Skore`.
+    pub module: String,
+    /// The location of the item the notification refers to, relative to the
+    /// URL of the Smartschool instance.
+    pub target: String,
+    /// The notification's title.
+    pub title: String,
+}
+
+/// A handle to a [`Notification`](crate::notifications::Notification).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct NotificationId(u64);
+
+impl NotificationId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for NotificationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for NotificationId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The kind of event a [`Notification`](crate::notifications::Notification) is
+/// about.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut messages = notifications::subscribe(&client).try_filter_map(|notification| async move {
+///     match notification.kind {
+///         NotificationKind::NewMessage { message_id } => Ok(Some(message_id)),
+///         _ => Ok(None),
+///     }
+/// });
+/// ```
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum NotificationKind {
+    /// A lesson in the agenda has changed.
+    #[serde(rename = "agenda", rename_all = "camelCase")]
+    AgendaChange {
+        /// The lesson's identifier.
+        lesson_id: LessonId,
+    },
+    /// A document has been published in a course's documents area.
+    #[serde(rename = "courseDocument", rename_all = "camelCase")]
+    NewDocument {
+        /// The course's identifier.
+        course_id: CourseId,
+        /// The document's identifier.
+        document_id: DocumentId,
+    },
+    /// A message has arrived.
+    #[serde(rename = "message", rename_all = "camelCase")]
+    NewMessage {
+        /// The message's identifier.
+        message_id: MessageId,
+    },
+    /// A result has been published.
+    #[serde(rename = "result", rename_all = "camelCase")]
+    NewResult {
+        /// The evaluation's identifier.
+        evaluation_id: EvaluationId,
+    },
+    /// A kind of event that isn't known to this crate.
+    #[default]
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct Poll {
+    cursor: String,
+    #[serde(default)]
+    notifications: Vec<Notification>,
+}
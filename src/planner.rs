@@ -0,0 +1,220 @@
+//! The planner, which replaces the classic [`agenda`](crate::agenda) on newer
+//! platforms.
+//!
+//! Use [`is_supported`](crate::planner::is_supported) to find out which of both
+//! modules a platform provides.
+
+use crate::{
+    error::{EntityKind, Error, Result},
+    http::{TryJson, TrySend},
+    Client,
+};
+use bytes::Bytes;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate};
+use futures::{Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// Downloads a planned item's attachment and returns its contents as a
+/// non-blocking stream of [`Bytes`](bytes::Bytes).
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The planned item doesn't exist.
+/// * The attachment doesn't exist or isn't associated with the planned item.
+pub async fn download_attachment(
+    client: &Client<'_>,
+    item_id: PlannedItemId,
+    attachment_id: PlannedAttachmentId,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let url = format!(
+        "{}/planner/api/v1/planned-elements/{}/attachments/{}/download",
+        client.url(),
+        item_id,
+        attachment_id
+    );
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_identified_entity(EntityKind::PlannedItem, item_id))?;
+    Ok(response.bytes_stream().err_into())
+}
+
+/// Returns a planned item.
+///
+/// # Errors
+///
+/// Returns an error if the planned item doesn't exist.
+pub async fn get_planned_item(client: &Client<'_>, id: PlannedItemId) -> Result<PlannedItem> {
+    let url = format!("{}/planner/api/v1/planned-elements/{}", client.url(), id);
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::PlannedItem, id))?
+        .try_json()
+        .await
+}
+
+/// Returns the planned items between two dates (both inclusive), sorted by
+/// start date in ascending order.
+pub async fn get_planned_items(
+    client: &Client<'_>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<PlannedItem>> {
+    let url = format!("{}/planner/api/v1/planned-elements", client.url());
+    client
+        .http_client()
+        .get(&url)
+        .query(&[("from", from.to_string()), ("to", to.to_string())])
+        .try_send()
+        .await?
+        .try_json()
+        .await
+}
+
+/// Returns the planned items in the week (Monday through Sunday) containing
+/// the specified date, sorted by start date in ascending order.
+///
+/// # Errors
+///
+/// Returns an error if the week is out of the range of dates that can be
+/// represented.
+pub async fn get_week(client: &Client<'_>, date: NaiveDate) -> Result<Vec<PlannedItem>> {
+    let monday =
+        date.checked_sub_signed(Duration::days(date.weekday().num_days_from_monday().into()));
+    let sunday = monday.and_then(|monday| monday.checked_add_signed(Duration::days(6)));
+    match (monday, sunday) {
+        (Some(monday), Some(sunday)) => get_planned_items(client, monday, sunday).await,
+        _ => Err(Error::Validation {
+            field: "date",
+            reason: "must be in a week within the range of representable dates".to_owned(),
+        }),
+    }
+}
+
+/// Returns `true` if the platform provides the planner. If it doesn't, the
+/// classic [`agenda`](crate::agenda) should be used instead.
+pub async fn is_supported(client: &Client<'_>) -> Result<bool> {
+    let url = format!("{}/planner/api/v1/settings", client.url());
+    match client.http_client().get(&url).try_send().await {
+        Ok(_) => Ok(true),
+        Err(err) if err.is_not_found() => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// A file attached to a planned item.
+#[derive(Clone, Debug, Deserialize, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedAttachment {
+    /// The attachment's identifier.
+    pub id: PlannedAttachmentId,
+    /// The attachment's MIME type.
+    pub mime_type: String,
+    /// The attachment's file name.
+    pub name: String,
+    /// The attachment's size in bytes.
+    pub size: u64,
+}
+
+/// A handle to a [`PlannedAttachment`](crate::planner::PlannedAttachment).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PlannedAttachmentId(Uuid);
+
+impl PlannedAttachmentId {
+    /// Returns the underlying [`Uuid`](uuid::Uuid).
+    pub fn as_inner(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl fmt::Display for PlannedAttachmentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<Uuid> for PlannedAttachmentId {
+    fn from(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+}
+
+/// An item in the planner, like a lesson or an assignment.
+#[derive(Clone, Debug, Deserialize, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedItem {
+    /// The files attached to the item.
+    #[serde(default)]
+    pub attachments: Vec<PlannedAttachment>,
+    /// The name of the item's course, if it is associated with one.
+    pub course: Option<String>,
+    /// The item's description, formatted as HTML.
+    #[serde(default)]
+    pub description: String,
+    /// The date when the item ends.
+    pub end: DateTime<FixedOffset>,
+    /// The item's identifier.
+    pub id: PlannedItemId,
+    /// Whether the logged-in user has marked the item as completed.
+    #[serde(default)]
+    pub is_completed: bool,
+    /// The kind of the item.
+    #[serde(rename = "type")]
+    pub kind: PlannedItemKind,
+    /// The date when the item starts.
+    pub start: DateTime<FixedOffset>,
+    /// The item's title.
+    pub title: String,
+}
+
+/// A handle to a [`PlannedItem`](crate::planner::PlannedItem).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PlannedItemId(Uuid);
+
+impl PlannedItemId {
+    /// Returns the underlying [`Uuid`](uuid::Uuid).
+    pub fn as_inner(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl fmt::Display for PlannedItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<Uuid> for PlannedItemId {
+    fn from(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+}
+
+/// The kind of a [`PlannedItem`](crate::planner::PlannedItem).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum PlannedItemKind {
+    /// An assignment, like homework or a test.
+    #[serde(rename = "planned-assignments")]
+    Assignment,
+    /// A lesson.
+    #[serde(rename = "planned-lessons")]
+    Lesson,
+    /// A placeholder reserving a slot, without any content yet.
+    #[serde(rename = "planned-placeholders")]
+    Placeholder,
+    /// A personal to-do item.
+    #[serde(rename = "planned-to-dos")]
+    ToDo,
+    /// A kind of item that isn't known to this crate.
+    #[serde(other)]
+    Other,
+}
@@ -0,0 +1,42 @@
+//! Polling utilities for streams that watch the server for changes.
+
+use crate::error::Result;
+use futures::{
+    stream::{self, BoxStream},
+    Future, StreamExt,
+};
+use futures_timer::Delay;
+use std::time::Duration;
+
+/// Returns a non-blocking stream that repeatedly calls `poll` and yields the
+/// items or the error it returns.
+///
+/// Every call but the first is preceded by the specified interval, including
+/// the calls following an error, so an unreachable server isn't polled in a
+/// tight loop. `poll` receives the state returned by the previous call, or
+/// `state` on the first call, and has to return the state for the next call
+/// along with its result. The stream never ends by itself.
+pub fn every<'a, S, T, F, Fut>(interval: Duration, state: S, poll: F) -> BoxStream<'a, Result<T>>
+where
+    S: Send + 'a,
+    T: Send + 'a,
+    F: FnMut(S) -> Fut + Send + 'a,
+    Fut: Future<Output = (S, Result<Vec<T>>)> + Send + 'a,
+{
+    stream::unfold(
+        (state, poll, true),
+        move |(state, mut poll, first)| async move {
+            if !first {
+                Delay::new(interval).await;
+            }
+            let (state, result) = poll(state).await;
+            let items = match result {
+                Ok(items) => items.into_iter().map(Ok).collect(),
+                Err(err) => vec![Err(err)],
+            };
+            Some((stream::iter(items), (state, poll, false)))
+        },
+    )
+    .flatten()
+    .boxed()
+}
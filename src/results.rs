@@ -0,0 +1,557 @@
+//! The results (grades) of the logged-in user, as published in Skore.
+
+use crate::{
+    agenda::SchoolYear,
+    courses::CourseId,
+    csv::CsvFormat,
+    error::{EntityKind, Error, Result},
+    http::{TryJson, TrySend},
+    serde::deserialize_lenient_number,
+    skore::PeriodId,
+    user::UserId,
+    Client,
+};
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use futures::{Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Reverse, collections::BTreeMap, fmt, io::Write};
+
+/// Downloads a published term report and returns its contents, a PDF document,
+/// as a non-blocking stream of [`Bytes`](bytes::Bytes).
+///
+/// # Errors
+///
+/// Returns an error if the report doesn't exist.
+pub async fn download_report(
+    client: &Client<'_>,
+    id: ReportId,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let url = format!("{}/results/api/v1/reports/{}/download", client.url(), id);
+    let response = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Report, id))?;
+    Ok(response.bytes_stream().err_into())
+}
+
+/// Fetches the evaluations matching a filter and writes them to a CSV file, one
+/// record per evaluation, preceded by a header record.
+///
+/// Scores on a scale are written as their label and feedback-only entries
+/// have an empty score.
+///
+/// # Errors
+///
+/// Returns an error if the format is invalid, the evaluations can't be fetched
+/// or the writer fails.
+pub async fn export_csv<W: Write>(
+    client: &Client<'_>,
+    filter: &ResultsFilter,
+    format: CsvFormat,
+    mut writer: W,
+) -> Result<()> {
+    format.validate()?;
+    let evaluations = get_results(client, filter).await?;
+
+    format.write_record(
+        &mut writer,
+        &[
+            "Date", "Course", "Title", "Score", "Max", "Weight", "Teacher", "Feedback",
+        ],
+    )?;
+    for evaluation in evaluations {
+        let number =
+            |value: Option<f64>| value.map(|value| format.number(value)).unwrap_or_default();
+        format.write_record(
+            &mut writer,
+            &[
+                evaluation.date.to_string(),
+                evaluation.course,
+                evaluation.title,
+                match evaluation.score {
+                    Score::FeedbackOnly => String::new(),
+                    Score::Numeric(score) => format.number(score),
+                    Score::Scale(label) => label,
+                },
+                number(evaluation.max),
+                format.number(evaluation.weight),
+                evaluation.teacher,
+                evaluation.feedback,
+            ],
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Returns the results per learning goal, for schools that grade on
+/// competencies rather than points, sorted by course and goal.
+pub async fn get_competency_results(client: &Client<'_>) -> Result<Vec<CompetencyResult>> {
+    let url = format!("{}/results/api/v1/competencies", client.url());
+    let mut results: Vec<CompetencyResult> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    results.sort_by(|a, b| (&a.course, a.goal).cmp(&(&b.course, b.goal)));
+    Ok(results)
+}
+
+/// Returns the published term reports of every school year, sorted by
+/// publication date in descending order.
+pub async fn get_reports(client: &Client<'_>) -> Result<Vec<Report>> {
+    let url = format!("{}/results/api/v1/reports", client.url());
+    let mut reports: Vec<Report> = client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    reports.sort_by_key(|report| Reverse(report.published));
+    Ok(reports)
+}
+
+/// Returns the details of an evaluation, including the scores per criterion of
+/// graded rubrics and the learning goals the evaluation is linked to.
+///
+/// # Errors
+///
+/// Returns an error if the evaluation doesn't exist.
+pub async fn get_result(client: &Client<'_>, id: EvaluationId) -> Result<EvaluationDetails> {
+    let url = format!("{}/results/api/v1/evaluations/{}", client.url(), id);
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Evaluation, id))?
+        .try_json()
+        .await
+}
+
+/// Returns the evaluations matching a filter, sorted by date in descending
+/// order.
+///
+/// # Errors
+///
+/// Returns an error if the filter's date range is empty.
+pub async fn get_results(client: &Client<'_>, filter: &ResultsFilter) -> Result<Vec<Evaluation>> {
+    if let (Some(since), Some(until)) = (filter.since, filter.until) {
+        if since > until {
+            return Err(Error::Validation {
+                field: "until",
+                reason: "must not be earlier than `since`".to_owned(),
+            });
+        }
+    }
+
+    let url = format!("{}/results/api/v1/evaluations", client.url());
+    let mut evaluations: Vec<Evaluation> = client
+        .http_client()
+        .get(&url)
+        .query(filter)
+        .try_send()
+        .await?
+        .try_json()
+        .await?;
+    // Not every platform filters by date, so the range is enforced here too.
+    evaluations.retain(|evaluation| {
+        filter.since.is_none_or(|since| evaluation.date >= since)
+            && filter.until.is_none_or(|until| evaluation.date <= until)
+    });
+    evaluations.sort_by_key(|evaluation| Reverse(evaluation.date));
+    Ok(evaluations)
+}
+
+/// Returns the weight of an evaluation whose weight isn't specified.
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// Returns an evaluation's obtained and maximum points multiplied by its
+/// weight.
+fn weighted_points(evaluation: &Evaluation) -> (f64, f64) {
+    let score = evaluation.score.as_f64().unwrap_or_default();
+    let max = evaluation.max.unwrap_or_default();
+    (score * evaluation.weight, max * evaluation.weight)
+}
+
+/// The level of achievement of a learning goal, on a scale defined by the
+/// school.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AchievementLevel {
+    /// The level's label, e.g. `Beheerst`.
+    pub label: String,
+    /// The highest rank on the scale.
+    pub max_rank: u8,
+    /// The level's position on the scale, where a higher rank indicates a
+    /// higher level of achievement.
+    pub rank: u8,
+}
+
+/// A learning goal an evaluation is linked to.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompetencyLink {
+    /// The learning goal's identifier.
+    pub goal: GoalId,
+    /// The learning goal's description.
+    pub name: String,
+}
+
+/// The result for a learning goal.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompetencyResult {
+    /// The name of the learning goal's course.
+    pub course: String,
+    /// The evaluations the result is based on.
+    #[serde(default)]
+    pub evaluations: Vec<EvaluationId>,
+    /// The learning goal's identifier.
+    pub goal: GoalId,
+    /// The level of achievement, or `None` if the goal hasn't been evaluated
+    /// yet.
+    pub level: Option<AchievementLevel>,
+    /// The learning goal's description.
+    pub name: String,
+}
+
+/// A criterion of a graded rubric.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Criterion {
+    /// The teacher's feedback on the criterion, or an empty string if there is
+    /// none.
+    #[serde(default)]
+    pub feedback: String,
+    /// The maximum score for the criterion, or `None` if it isn't graded on
+    /// points.
+    #[serde(default, deserialize_with = "deserialize_lenient_number")]
+    pub max: Option<f64>,
+    /// The criterion's name.
+    pub name: String,
+    /// The obtained score for the criterion.
+    #[serde(default)]
+    pub score: Score,
+}
+
+/// A graded evaluation, such as a test or an assignment.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Evaluation {
+    /// The name of the evaluation's course.
+    pub course: String,
+    /// The date of the evaluation.
+    pub date: NaiveDate,
+    /// The teacher's feedback, or an empty string if there is none.
+    #[serde(default)]
+    pub feedback: String,
+    /// The evaluation's identifier.
+    pub id: EvaluationId,
+    /// The maximum score, or `None` if the evaluation isn't graded on points.
+    #[serde(default, deserialize_with = "deserialize_lenient_number")]
+    pub max: Option<f64>,
+    /// The obtained score.
+    #[serde(default)]
+    pub score: Score,
+    /// The name of the teacher who graded the evaluation.
+    pub teacher: String,
+    /// The evaluation's title.
+    pub title: String,
+    /// The evaluation's weight in the course's total, usually `1`.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+impl Evaluation {
+    /// Returns the obtained score as a fraction of the maximum score, between
+    /// `0` and `1`, or `None` if the evaluation isn't graded on points.
+    pub fn fraction(&self) -> Option<f64> {
+        match (&self.score, self.max) {
+            (Score::Numeric(score), Some(max)) if max > 0.0 => Some(score / max),
+            _ => None,
+        }
+    }
+}
+
+/// An evaluation, as returned by [`get_result`](crate::results::get_result).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationDetails {
+    /// The learning goals the evaluation is linked to.
+    #[serde(default)]
+    pub competencies: Vec<CompetencyLink>,
+    /// The scores per criterion, or an empty vector if the evaluation isn't
+    /// graded using a rubric.
+    #[serde(default)]
+    pub criteria: Vec<Criterion>,
+    /// The evaluation's summary, as included in listings.
+    #[serde(flatten)]
+    pub evaluation: Evaluation,
+}
+
+/// A handle to an [`Evaluation`](crate::results::Evaluation).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct EvaluationId(u64);
+
+impl EvaluationId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for EvaluationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for EvaluationId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A learning goal's identifier.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct GoalId(u64);
+
+impl GoalId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for GoalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for GoalId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A published term report.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    /// The report's identifier.
+    pub id: ReportId,
+    /// The report's name, usually the name of the term it covers.
+    pub name: String,
+    /// The date when the report was published.
+    pub published: DateTime<FixedOffset>,
+    /// The school year the report belongs to.
+    pub school_year: SchoolYear,
+}
+
+/// A handle to a [`Report`](crate::results::Report).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct ReportId(u64);
+
+impl ReportId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ReportId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for ReportId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A filter used to [`get_results`](crate::results::get_results).
+///
+/// Filters are combined, so an evaluation has to match all of them to be
+/// returned. An empty filter matches every evaluation.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultsFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    child_id: Option<UserId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    course_id: Option<CourseId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    period_id: Option<PeriodId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    since: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    until: Option<NaiveDate>,
+}
+
+impl ResultsFilter {
+    /// Creates an empty filter.
+    pub fn new() -> ResultsFilter {
+        ResultsFilter::default()
+    }
+
+    /// Only matches evaluations of the specified child, for parent accounts
+    /// with several children.
+    pub fn child(mut self, child_id: UserId) -> ResultsFilter {
+        self.child_id = Some(child_id);
+        self
+    }
+
+    /// Only matches evaluations of the specified course.
+    pub fn course(mut self, course_id: CourseId) -> ResultsFilter {
+        self.course_id = Some(course_id);
+        self
+    }
+
+    /// Only matches evaluations in the specified period of the school year.
+    pub fn period(mut self, period_id: PeriodId) -> ResultsFilter {
+        self.period_id = Some(period_id);
+        self
+    }
+
+    /// Only matches evaluations on or after the specified date.
+    pub fn since(mut self, date: NaiveDate) -> ResultsFilter {
+        self.since = Some(date);
+        self
+    }
+
+    /// Only matches evaluations on or before the specified date.
+    pub fn until(mut self, date: NaiveDate) -> ResultsFilter {
+        self.until = Some(date);
+        self
+    }
+}
+
+/// Aggregate statistics on evaluations.
+///
+/// Only evaluations graded on points with a positive maximum score and weight
+/// are taken into account. Evaluations that aren't, such as those graded on a
+/// plus/minus scale or containing only feedback, are counted as
+/// [`skipped`](crate::results::ResultsSummary::skipped).
+#[derive(Clone, Debug)]
+pub struct ResultsSummary {
+    evaluations: Vec<Evaluation>,
+    skipped: usize,
+}
+
+impl ResultsSummary {
+    /// Creates a summary of the specified evaluations.
+    pub fn new<I: IntoIterator<Item = Evaluation>>(evaluations: I) -> Self {
+        let mut skipped = 0;
+        let mut graded: Vec<Evaluation> = evaluations
+            .into_iter()
+            .filter(|evaluation| {
+                let is_graded = evaluation.fraction().is_some() && evaluation.weight > 0.0;
+                if !is_graded {
+                    skipped += 1;
+                }
+                is_graded
+            })
+            .collect();
+        graded.sort_by_key(|evaluation| evaluation.date);
+        Self {
+            evaluations: graded,
+            skipped,
+        }
+    }
+
+    /// Returns the weighted average per course as a fraction between `0` and
+    /// `1`, keyed by course name.
+    pub fn per_course_average(&self) -> BTreeMap<String, f64> {
+        let mut totals: BTreeMap<String, (f64, f64)> = BTreeMap::new();
+        for evaluation in &self.evaluations {
+            let (score, max) = weighted_points(evaluation);
+            let total = totals.entry(evaluation.course.clone()).or_default();
+            total.0 += score;
+            total.1 += max;
+        }
+        totals
+            .into_iter()
+            .map(|(course, (score, max))| (course, score / max))
+            .collect()
+    }
+
+    /// Returns the number of evaluations that aren't taken into account.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Returns the weighted average after every date on which an evaluation
+    /// took place, as a fraction between `0` and `1`, sorted by date in
+    /// ascending order.
+    pub fn trend(&self) -> Vec<(NaiveDate, f64)> {
+        let mut trend: Vec<(NaiveDate, f64)> = Vec::new();
+        let (mut score, mut max) = (0.0, 0.0);
+        for evaluation in &self.evaluations {
+            let points = weighted_points(evaluation);
+            score += points.0;
+            max += points.1;
+            match trend.last_mut() {
+                Some(last) if last.0 == evaluation.date => last.1 = score / max,
+                _ => trend.push((evaluation.date, score / max)),
+            }
+        }
+        trend
+    }
+
+    /// Returns the weighted average over all evaluations as a fraction between
+    /// `0` and `1`, or `None` if no evaluations are taken into account.
+    pub fn weighted_average(&self) -> Option<f64> {
+        let (score, max) = self.weighted_total();
+        if max > 0.0 {
+            Some(score / max)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the weighted obtained and maximum points over all evaluations.
+    pub fn weighted_total(&self) -> (f64, f64) {
+        self.evaluations
+            .iter()
+            .map(weighted_points)
+            .fold((0.0, 0.0), |total, points| {
+                (total.0 + points.0, total.1 + points.1)
+            })
+    }
+}
+
+/// The score of an evaluation or a rubric criterion.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Score {
+    /// The evaluation only contains feedback and no score.
+    #[default]
+    FeedbackOnly,
+    /// A score in points.
+    Numeric(f64),
+    /// A score on a scale that isn't expressed in points, containing the
+    /// scale's label, e.g. `++`, `A` or `NA`.
+    Scale(String),
+}
+
+impl Score {
+    /// Returns the score in points, or `None` if the score isn't expressed in
+    /// points.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Score::Numeric(score) => Some(*score),
+            _ => None,
+        }
+    }
+}
@@ -1,7 +1,11 @@
 //! Custom [`Serialize`](serde::ser::Serialize) and
 //! [`Deserialize`](serde::de::Deserialize) implementations.
 
-use crate::mydoc::{CustomFolderId, FolderColor, FolderId};
+use crate::{
+    messages::{Flag, MailBox},
+    mydoc::{CustomFolderId, FolderColor, FolderId},
+    results::Score,
+};
 use serde::{
     de::{self, Deserialize, Deserializer, Visitor},
     ser::{Serialize, Serializer},
@@ -53,19 +57,173 @@ impl Serialize for FolderId {
     }
 }
 
+impl Serialize for MailBox {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes an optional identifier that may be sent as a string or as an
+/// integer.
+pub fn deserialize_optional_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    deserializer.deserialize_any(OptionalIdVisitor)
+}
+
+/// Deserializes an optional number that may be sent as a string, possibly
+/// using a comma as the decimal separator. Strings that aren't numbers, such as
+/// `NA` or `+`, are deserialized as `None`.
+pub fn deserialize_lenient_number<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<f64>, D::Error> {
+    deserializer.deserialize_any(LenientNumberVisitor)
+}
+
+struct LenientNumberVisitor;
+
+impl<'de> Visitor<'de> for LenientNumberVisitor {
+    type Value = Option<f64>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a number, a string or null")
+    }
+
+    fn visit_f64<E: de::Error>(self, n: f64) -> Result<Self::Value, E> {
+        Ok(Some(n))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+        Ok(Some(n as f64))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        Ok(s.trim()
+            .replace(',', ".")
+            .parse()
+            .ok()
+            .filter(|n: &f64| n.is_finite()))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+        Ok(Some(n as f64))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+}
+
+struct OptionalIdVisitor;
+
+impl<'de> Visitor<'de> for OptionalIdVisitor {
+    type Value = Option<String>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a string, an integer or null")
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+        Ok(Some(n.to_string()))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        Ok(Some(s.to_owned()))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+        Ok(Some(n.to_string()))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+}
+
+impl<'de> Deserialize<'de> for Score {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ScoreVisitor)
+    }
+}
+
+struct ScoreVisitor;
+
+impl<'de> Visitor<'de> for ScoreVisitor {
+    type Value = Score;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a number, a string or null")
+    }
+
+    fn visit_f64<E: de::Error>(self, n: f64) -> Result<Self::Value, E> {
+        Ok(Score::Numeric(n))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+        Ok(Score::Numeric(n as f64))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Score::FeedbackOnly)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(Score::FeedbackOnly);
+        }
+        match LenientNumberVisitor.visit_str::<E>(s)? {
+            Some(n) => Ok(Score::Numeric(n)),
+            None => Ok(Score::Scale(s.to_owned())),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+        Ok(Score::Numeric(n as f64))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Score::FeedbackOnly)
+    }
+}
+
+impl Serialize for Score {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Score::FeedbackOnly => serializer.serialize_none(),
+            Score::Numeric(n) => serializer.serialize_f64(*n),
+            Score::Scale(label) => serializer.serialize_str(label),
+        }
+    }
+}
+
 /// An enum representing the types that can be serialized as JSON.
 #[derive(Debug)]
 pub enum Json<'a> {
+    Bool(bool),
     FolderColor(FolderColor),
     FolderId(FolderId),
+    MailBox(MailBox),
+    MessageFlag(Flag),
     Str(&'a str),
 }
 
 impl<'a> Serialize for Json<'a> {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         match self {
+            Json::Bool(b) => serializer.serialize_bool(*b),
             Json::FolderColor(color) => color.serialize(serializer),
             Json::FolderId(id) => id.serialize(serializer),
+            Json::MailBox(mailbox) => mailbox.serialize(serializer),
+            Json::MessageFlag(flag) => flag.serialize(serializer),
             Json::Str(s) => serializer.serialize_str(s),
         }
     }
@@ -0,0 +1,233 @@
+//! The teacher-side gradebook, known as Skore.
+//!
+//! Students and parents can view their published grades using the
+//! [`results`](crate::results) module.
+
+use crate::{
+    courses::CourseId,
+    error::{EntityKind, Error, Result},
+    http::{encode_segment, TryJson, TrySend},
+    user::{ClassId, UserId},
+    Client,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Enters a student's score for an evaluation column, replacing any score that
+/// was entered before.
+///
+/// # Errors
+///
+/// Returns an error if the score is negative or not a number, or the column
+/// doesn't exist.
+pub async fn enter_result(
+    client: &Client<'_>,
+    column_id: ColumnId,
+    student_id: &UserId,
+    score: f64,
+) -> Result<()> {
+    validate_score(score)?;
+
+    let result = ResultEntry {
+        score,
+        student: student_id,
+    };
+    let url = format!(
+        "{}/skore/api/v1/columns/{}/results/{}",
+        client.url(),
+        column_id,
+        encode_segment(student_id.as_str())
+    );
+    client
+        .http_client()
+        .put(&url)
+        .json(&result)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::GradebookColumn, column_id))?;
+    Ok(())
+}
+
+/// Enters the scores of many students for an evaluation column at once,
+/// replacing any scores that were entered before.
+///
+/// The scores are entered in a single request, so either all or none of them
+/// are entered.
+///
+/// # Errors
+///
+/// Returns an error if one of the scores is negative or not a number, or the
+/// column doesn't exist.
+pub async fn enter_results<'a, I>(client: &Client<'_>, column_id: ColumnId, scores: I) -> Result<()>
+where
+    I: IntoIterator<Item = (&'a UserId, f64)>,
+{
+    let results = scores
+        .into_iter()
+        .map(|(student, score)| {
+            validate_score(score)?;
+            Ok(ResultEntry { score, student })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let url = format!(
+        "{}/skore/api/v1/columns/{}/results",
+        client.url(),
+        column_id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .json(&results)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::GradebookColumn, column_id))?;
+    Ok(())
+}
+
+/// Returns the structure of a course's gradebook, i.e. its evaluation columns,
+/// periods and students.
+///
+/// # Errors
+///
+/// Returns an error if the course doesn't exist or the logged-in user isn't
+/// allowed to view its gradebook.
+pub async fn get_gradebook(client: &Client<'_>, course_id: CourseId) -> Result<Gradebook> {
+    let url = format!(
+        "{}/skore/api/v1/courses/{}/gradebook",
+        client.url(),
+        course_id
+    );
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Course, course_id))?
+        .try_json()
+        .await
+}
+
+/// Checks that a score can be entered in the gradebook.
+fn validate_score(score: f64) -> Result<()> {
+    if score.is_finite() && score >= 0.0 {
+        Ok(())
+    } else {
+        Err(Error::Validation {
+            field: "score",
+            reason: "must be a non-negative number".to_owned(),
+        })
+    }
+}
+
+/// An evaluation column in the gradebook.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Column {
+    /// The date of the evaluation, if any.
+    pub date: Option<NaiveDate>,
+    /// The column's identifier.
+    pub id: ColumnId,
+    /// The maximum score.
+    pub max: f64,
+    /// The evaluation's name.
+    pub name: String,
+    /// The period the evaluation belongs to.
+    pub period: PeriodId,
+    /// The evaluation's weight in the period's total.
+    pub weight: f64,
+}
+
+/// A handle to an evaluation column in the gradebook.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct ColumnId(u64);
+
+impl ColumnId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ColumnId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for ColumnId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The structure of a course's gradebook.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gradebook {
+    /// The evaluation columns, in the order they're displayed.
+    pub columns: Vec<Column>,
+    /// The periods the school year is divided into, such as terms or exam
+    /// periods.
+    pub periods: Vec<Period>,
+    /// The students taking the course.
+    pub students: Vec<Student>,
+}
+
+/// A period of the school year in the gradebook.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Period {
+    /// The last day of the period.
+    pub end: NaiveDate,
+    /// The period's identifier.
+    pub id: PeriodId,
+    /// The period's name.
+    pub name: String,
+    /// The first day of the period.
+    pub start: NaiveDate,
+    /// The period's weight in the year's total.
+    pub weight: f64,
+}
+
+/// A handle to a [`Period`](crate::skore::Period).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct PeriodId(u64);
+
+impl PeriodId {
+    /// Returns the underlying integer.
+    pub fn as_inner(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for PeriodId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<u64> for PeriodId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Serialize)]
+struct ResultEntry<'a> {
+    score: f64,
+    student: &'a UserId,
+}
+
+/// A student on a gradebook's roster.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Student {
+    /// The student's class.
+    pub class: ClassId,
+    /// The student's identifier.
+    pub id: UserId,
+    /// The student's full name.
+    pub name: String,
+}
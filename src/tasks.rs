@@ -0,0 +1,654 @@
+//! Homework and tests, regardless of whether the platform provides the classic
+//! [`agenda`](crate::agenda) or the [`planner`](crate::planner).
+
+use crate::{
+    agenda::{self, TaskKind},
+    courses::CourseId,
+    error::{EntityKind, Error, Result},
+    file::{self, UniquePaths},
+    http::{TryJson, TrySend},
+    planner::{self, PlannedAttachment, PlannedItem, PlannedItemId, PlannedItemKind},
+    serde::Json,
+    upload::{self, File, UploadDirectory},
+    user::{ClassId, UserId},
+    Client,
+};
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+/// Publishes an assignment in the planner and returns the created planned item.
+///
+/// Only teachers of the assignment's course are allowed to create assignments.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * The assignment's title is empty or it doesn't target any classes.
+/// * One of the files has an [illegal file
+///   name](crate::upload::upload_file).
+/// * The course doesn't exist or the logged-in user isn't allowed to create
+///   assignments for it.
+pub async fn create(client: &Client<'_>, assignment: AssignmentBuilder) -> Result<PlannedItem> {
+    if assignment.title.trim().is_empty() {
+        return Err(Error::Validation {
+            field: "title",
+            reason: "must not be empty".to_owned(),
+        });
+    }
+    if assignment.classes.is_empty() {
+        return Err(Error::Validation {
+            field: "classes",
+            reason: "must contain at least one class".to_owned(),
+        });
+    }
+
+    let upload_dir = if assignment.files.is_empty() {
+        None
+    } else {
+        let upload_dir = upload::get_upload_directory(client).await?;
+        for file in assignment.files {
+            upload::upload_file(client, upload_dir.clone(), file).await?;
+        }
+        Some(upload_dir)
+    };
+
+    let new_assignment = NewAssignment {
+        classes: &assignment.classes,
+        course: assignment.course,
+        description: &assignment.description,
+        end: assignment.due,
+        title: &assignment.title,
+        upload_dir,
+    };
+    let url = format!("{}/planner/api/v1/planned-assignments", client.url());
+    client
+        .http_client()
+        .post(&url)
+        .json(&new_assignment)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::Course, new_assignment.course))?
+        .try_json()
+        .await
+}
+
+/// Downloads the attachments of all tasks from the planner that are due in the
+/// specified range of dates, and returns the paths of the written files.
+///
+/// The attachments of each task are written to a folder named after the
+/// task's due date and title, inside a folder named after its course. The
+/// identifier of the task is appended to the folder's name if another task has
+/// the same due date and title.
+///
+/// # Errors
+///
+/// Returns an error if the tasks can't be fetched, an attachment can't be
+/// downloaded or a file can't be written.
+pub async fn download_all_attachments<P: AsRef<Path>>(
+    client: &Client<'_>,
+    range: RangeInclusive<NaiveDate>,
+    dir: P,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut task_dirs = UniquePaths::default();
+    for task in get_tasks(client, range).await? {
+        let item_id = match task.planned_item {
+            Some(item_id) if !task.attachments.is_empty() => item_id,
+            _ => continue,
+        };
+        let task_dir = dir
+            .as_ref()
+            .join(file::sanitize_name(&task.course))
+            .join(file::sanitize_name(&format!("{} {}", task.due, task.title)));
+        let task_dir = task_dirs.folder(task_dir, item_id);
+        paths.extend(write_attachments(client, item_id, &task.attachments, &task_dir).await?);
+    }
+    Ok(paths)
+}
+
+/// Downloads the attachments of a task from the planner to a folder, and
+/// returns the paths of the written files.
+///
+/// Files are written to a temporary file first, so an interrupted download
+/// never leaves a partial file behind under the attachment's name. The
+/// identifier of an attachment is appended to its file name if another
+/// attachment has the same name.
+///
+/// # Errors
+///
+/// Returns an error if the planned item doesn't exist, an attachment can't be
+/// downloaded or a file can't be written.
+pub async fn download_attachments<P: AsRef<Path>>(
+    client: &Client<'_>,
+    task_id: PlannedItemId,
+    dir: P,
+) -> Result<Vec<PathBuf>> {
+    let item = planner::get_planned_item(client, task_id).await?;
+    write_attachments(client, task_id, &item.attachments, dir.as_ref()).await
+}
+
+/// Returns the tasks of the logged-in user that aren't completed and are due
+/// from today up to the specified horizon (inclusive), sorted by due date in
+/// ascending order.
+///
+/// Today is determined using the local time zone.
+///
+/// # Errors
+///
+/// Returns an error if the end of the horizon is out of the range of dates that
+/// can be represented.
+pub async fn due_within(client: &Client<'_>, horizon: Duration) -> Result<Vec<Task>> {
+    let today = today();
+    let last_day = today
+        .checked_add_signed(horizon)
+        .ok_or_else(|| Error::Validation {
+            field: "horizon",
+            reason: "must be within the range of representable dates".to_owned(),
+        })?;
+    let mut tasks = get_tasks(client, today..=last_day).await?;
+    tasks.retain(|task| task.status == TaskStatus::Open);
+    Ok(tasks)
+}
+
+/// Returns the submission and completion state of an assignment for each
+/// student it is assigned to.
+///
+/// Only teachers of the assignment's course are allowed to view its
+/// submissions.
+///
+/// # Errors
+///
+/// Returns an error if the planned item doesn't exist or the logged-in user
+/// isn't allowed to view its submissions.
+pub async fn get_submission_stats(
+    client: &Client<'_>,
+    task_id: PlannedItemId,
+) -> Result<SubmissionStats> {
+    let url = format!(
+        "{}/planner/api/v1/planned-assignments/{}/submissions",
+        client.url(),
+        task_id
+    );
+    client
+        .http_client()
+        .get(&url)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::PlannedItem, task_id))?
+        .try_json()
+        .await
+}
+
+/// Returns the tasks of the logged-in user that are due in the specified range
+/// of dates, sorted by due date in ascending order.
+///
+/// The tasks are taken from the planner if the platform provides it, and from
+/// the classic agenda otherwise. Due dates of tasks from the planner are
+/// determined in the platform's time zone, so a task due at midnight doesn't
+/// move to another day for users abroad. Statuses are determined relative to
+/// today in the local time zone.
+pub async fn get_tasks(client: &Client<'_>, range: RangeInclusive<NaiveDate>) -> Result<Vec<Task>> {
+    let (from, to) = range.into_inner();
+    let today = today();
+
+    let mut tasks: Vec<Task> = if planner::is_supported(client).await? {
+        planner::get_planned_items(client, from, to)
+            .await?
+            .into_iter()
+            .filter(|item| item.kind == PlannedItemKind::Assignment)
+            .map(|item| {
+                let due = item.end.date_naive();
+                Task {
+                    attachments: item.attachments,
+                    course: item.course.unwrap_or_default(),
+                    description: item.description,
+                    due,
+                    kind: None,
+                    planned_item: Some(item.id),
+                    status: TaskStatus::new(item.is_completed, true, due, today),
+                    title: item.title,
+                }
+            })
+            .collect()
+    } else {
+        agenda::get_tasks(client, from, to)
+            .await?
+            .into_iter()
+            .map(|task| Task {
+                attachments: Vec::new(),
+                course: task.course,
+                description: task.description,
+                due: task.due,
+                kind: Some(task.kind),
+                planned_item: None,
+                status: TaskStatus::new(false, false, task.due, today),
+                title: String::new(),
+            })
+            .collect()
+    };
+    tasks.sort_by_key(|task| task.due);
+    Ok(tasks)
+}
+
+/// Groups tasks by the name of their course, keeping the order of the tasks
+/// within each course.
+///
+/// Tasks that aren't associated with a course are grouped under an empty
+/// string.
+pub fn group_by_course(tasks: Vec<Task>) -> BTreeMap<String, Vec<Task>> {
+    let mut groups: BTreeMap<String, Vec<Task>> = BTreeMap::new();
+    for task in tasks {
+        groups.entry(task.course.clone()).or_default().push(task);
+    }
+    groups
+}
+
+/// Returns the tasks of the logged-in user that aren't completed and were due
+/// from the specified date up to yesterday, sorted by due date in ascending
+/// order.
+///
+/// Tasks in the classic agenda can't be marked as completed, so they're never
+/// overdue. Today is determined using the local time zone.
+pub async fn overdue(client: &Client<'_>, since: NaiveDate) -> Result<Vec<Task>> {
+    let yesterday = today() - Duration::days(1);
+    let mut tasks = get_tasks(client, since..=yesterday).await?;
+    tasks.retain(|task| task.status == TaskStatus::Overdue);
+    Ok(tasks)
+}
+
+/// Marks a task from the planner as completed or not completed, like the
+/// checkbox next to the task.
+///
+/// Tasks in the classic agenda can't be marked as completed.
+///
+/// # Errors
+///
+/// Returns an error if the planned item doesn't exist.
+pub async fn set_done(client: &Client<'_>, task_id: PlannedItemId, done: bool) -> Result<()> {
+    let mut form = HashMap::new();
+    form.insert("isCompleted", Json::Bool(done));
+
+    let url = format!(
+        "{}/planner/api/v1/planned-elements/{}/completion",
+        client.url(),
+        task_id
+    );
+    client
+        .http_client()
+        .put(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::PlannedItem, task_id))?;
+    Ok(())
+}
+
+/// Hands in files for a task from the planner, along with a comment for the
+/// teacher, and marks the task as submitted.
+///
+/// Submitting again adds the files to the earlier submission. Tasks in the
+/// classic agenda can't be submitted; use
+/// [`courses::submit`](crate::courses::submit) to hand in files in an
+/// uploadzone instead.
+///
+/// # Errors
+///
+/// Returns an error in the following situations:
+///
+/// * No files are specified.
+/// * One of the files has an [illegal file
+///   name](crate::upload::upload_file).
+/// * The planned item doesn't exist or doesn't accept submissions.
+pub async fn submit(
+    client: &Client<'_>,
+    task_id: PlannedItemId,
+    files: Vec<File>,
+    comment: &str,
+) -> Result<()> {
+    if files.is_empty() {
+        return Err(Error::Validation {
+            field: "files",
+            reason: "must contain at least one file".to_owned(),
+        });
+    }
+
+    let upload_dir = upload::get_upload_directory(client).await?;
+    for file in files {
+        upload::upload_file(client, upload_dir.clone(), file).await?;
+    }
+
+    let mut form = HashMap::new();
+    form.insert("comment", Json::Str(comment));
+    form.insert("uploadDir", Json::Str(upload_dir.as_str()));
+
+    let url = format!(
+        "{}/planner/api/v1/planned-elements/{}/submissions",
+        client.url(),
+        task_id
+    );
+    client
+        .http_client()
+        .post(&url)
+        .json(&form)
+        .try_send()
+        .await
+        .map_err(|err| err.for_entity(EntityKind::PlannedItem, task_id))?;
+    Ok(())
+}
+
+/// Synchronizes the tasks that are due in the specified range of dates with an
+/// external task system.
+///
+/// The completion state of each task is compared with the state in the
+/// external system. If they differ, the conflict rule decides which state
+/// wins, and the state is written back to Smartschool if needed. Tasks in the
+/// classic agenda can't be marked as completed in Smartschool, so the external
+/// state always wins for them. Finally, every task is written to the external
+/// system along with its resolved state.
+///
+/// # Errors
+///
+/// Returns an error if the tasks can't be fetched, a completion state can't be
+/// written back or the external system returns an error.
+pub async fn sync<E: TaskSink + TaskSource>(
+    client: &Client<'_>,
+    range: RangeInclusive<NaiveDate>,
+    external: &mut E,
+    rule: ConflictRule,
+) -> Result<SyncSummary> {
+    let today = today();
+    let tasks = get_tasks(client, range).await?;
+    let completion = external.completion().await?;
+
+    let mut summary = SyncSummary::default();
+    for mut task in tasks {
+        let key = task.key();
+        let is_completed = task.status == TaskStatus::Completed;
+        if let Some(&is_completed_externally) = completion.get(&key) {
+            let resolved = match (task.planned_item, rule) {
+                (None, _) | (_, ConflictRule::PreferExternal) => is_completed_externally,
+                (_, ConflictRule::PreferCompleted) => is_completed || is_completed_externally,
+                (_, ConflictRule::PreferSmartschool) => is_completed,
+            };
+            if resolved != is_completed {
+                if let Some(item_id) = task.planned_item {
+                    set_done(client, item_id, resolved).await?;
+                    summary.written_back += 1;
+                }
+                task.status =
+                    TaskStatus::new(resolved, task.planned_item.is_some(), task.due, today);
+            }
+        }
+        external.put(&key, &task).await?;
+        summary.written += 1;
+    }
+    Ok(summary)
+}
+
+/// Returns today's date in the local time zone, relative to which the statuses
+/// of tasks are determined.
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Downloads attachments of a planned item to a folder.
+async fn write_attachments(
+    client: &Client<'_>,
+    item_id: PlannedItemId,
+    attachments: &[PlannedAttachment],
+    dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::with_capacity(attachments.len());
+    let mut unique_paths = UniquePaths::default();
+    for attachment in attachments {
+        let path = dir.join(file::sanitize_name(&attachment.name));
+        let path = unique_paths.file(path, attachment.id);
+        let bytes = planner::download_attachment(client, item_id, attachment.id).await?;
+        file::write(&path, bytes).await?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// A builder to construct an assignment, as published by
+/// [`create`](crate::tasks::create).
+pub struct AssignmentBuilder {
+    classes: Vec<ClassId>,
+    course: CourseId,
+    description: String,
+    due: DateTime<FixedOffset>,
+    files: Vec<File>,
+    title: String,
+}
+
+impl AssignmentBuilder {
+    /// Creates an assignment for a course with the specified title and due
+    /// date, without any target classes, description or files.
+    pub fn new<T: Into<String>>(
+        course_id: CourseId,
+        title: T,
+        due: DateTime<FixedOffset>,
+    ) -> AssignmentBuilder {
+        AssignmentBuilder {
+            classes: Vec::new(),
+            course: course_id,
+            description: String::new(),
+            due,
+            files: Vec::new(),
+            title: title.into(),
+        }
+    }
+
+    /// Assigns the assignment to a class. Can be called multiple times to
+    /// target several classes.
+    pub fn class(mut self, class_id: ClassId) -> AssignmentBuilder {
+        self.classes.push(class_id);
+        self
+    }
+
+    /// Sets the assignment's description, formatted as HTML.
+    pub fn description<T: Into<String>>(mut self, description: T) -> AssignmentBuilder {
+        self.description = description.into();
+        self
+    }
+
+    /// Attaches a file to the assignment. Can be called multiple times to
+    /// attach several files.
+    pub fn file(mut self, file: File) -> AssignmentBuilder {
+        self.files.push(file);
+        self
+    }
+}
+
+/// A rule deciding which completion state wins when Smartschool and an external
+/// task system disagree, as used by [`sync`](crate::tasks::sync).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ConflictRule {
+    /// A task is completed if it is completed on either side. This is the
+    /// default, as it never loses work that was checked off.
+    #[default]
+    PreferCompleted,
+    /// The external system's state wins.
+    PreferExternal,
+    /// Smartschool's state wins.
+    PreferSmartschool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NewAssignment<'a> {
+    classes: &'a [ClassId],
+    course: CourseId,
+    description: &'a str,
+    end: DateTime<FixedOffset>,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upload_dir: Option<UploadDirectory>,
+}
+
+/// The progress of a student on an assignment.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StudentProgress {
+    /// The student's class.
+    pub class: ClassId,
+    /// The student's identifier.
+    pub id: UserId,
+    /// Whether the student has marked the assignment as completed.
+    #[serde(default)]
+    pub is_completed: bool,
+    /// The student's full name.
+    pub name: String,
+    /// The date when the student last handed in files, or `None` if they
+    /// haven't handed in anything.
+    pub submitted_at: Option<DateTime<FixedOffset>>,
+}
+
+/// The progress of all students on an assignment, as returned by
+/// [`get_submission_stats`](crate::tasks::get_submission_stats).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmissionStats {
+    /// The students the assignment is assigned to.
+    pub students: Vec<StudentProgress>,
+}
+
+impl SubmissionStats {
+    /// Returns the students who haven't handed in anything yet, e.g. to send
+    /// them a reminder.
+    pub fn missing(&self) -> impl Iterator<Item = &StudentProgress> {
+        self.students
+            .iter()
+            .filter(|student| student.submitted_at.is_none())
+    }
+
+    /// Returns the number of students who have handed in files.
+    pub fn submitted(&self) -> usize {
+        self.students.len() - self.missing().count()
+    }
+}
+
+/// The outcome of [`sync`](crate::tasks::sync).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SyncSummary {
+    /// The number of tasks that were written to the external system.
+    pub written: usize,
+    /// The number of completion states that were written back to Smartschool.
+    pub written_back: usize,
+}
+
+/// A task assigned to the logged-in user, like homework or a test.
+#[derive(Clone, Debug, Hash)]
+pub struct Task {
+    /// The files attached to the task. Only tasks from the planner can have
+    /// attachments, which can be downloaded using
+    /// [`planner::download_attachment`](crate::planner::download_attachment).
+    pub attachments: Vec<PlannedAttachment>,
+    /// The name of the task's course, or an empty string if it isn't
+    /// associated with one.
+    pub course: String,
+    /// The task's description. Descriptions of tasks from the planner are
+    /// formatted as HTML.
+    pub description: String,
+    /// The date when the task is due.
+    pub due: NaiveDate,
+    /// The kind of the task, or `None` if the task is taken from the planner,
+    /// which doesn't distinguish between homework and tests.
+    pub kind: Option<TaskKind>,
+    /// The planned item the task is taken from, or `None` if it is taken from
+    /// the classic agenda.
+    pub planned_item: Option<PlannedItemId>,
+    /// The task's status.
+    pub status: TaskStatus,
+    /// The task's title, or an empty string if it is taken from the classic
+    /// agenda, which doesn't give tasks a title.
+    pub title: String,
+}
+
+impl Task {
+    /// Returns a key identifying the task across synchronizations.
+    pub fn key(&self) -> TaskKey {
+        match self.planned_item {
+            Some(item_id) => TaskKey::Planner(item_id),
+            None => TaskKey::Agenda {
+                course: self.course.clone(),
+                description: self.description.clone(),
+                due: self.due,
+            },
+        }
+    }
+}
+
+/// A key identifying a [`Task`](crate::tasks::Task) across synchronizations.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TaskKey {
+    /// A task in the classic agenda, which doesn't give tasks an identifier.
+    Agenda {
+        /// The name of the task's course.
+        course: String,
+        /// The task's description.
+        description: String,
+        /// The date when the task is due.
+        due: NaiveDate,
+    },
+    /// A task in the planner.
+    Planner(PlannedItemId),
+}
+
+/// An external task system that tasks are written to by
+/// [`sync`](crate::tasks::sync).
+///
+/// Errors specific to the external system can be returned as
+/// [`Error::External`](crate::Error::External).
+pub trait TaskSink {
+    /// Creates or updates the task with the specified key.
+    fn put<'a>(&'a mut self, key: &'a TaskKey, task: &'a Task) -> BoxFuture<'a, Result<()>>;
+}
+
+/// An external task system whose completion state is read by
+/// [`sync`](crate::tasks::sync).
+///
+/// Errors specific to the external system can be returned as
+/// [`Error::External`](crate::Error::External).
+pub trait TaskSource {
+    /// Returns whether the tasks known to the external system are completed,
+    /// keyed by the keys they were written with.
+    fn completion(&mut self) -> BoxFuture<'_, Result<HashMap<TaskKey, bool>>>;
+}
+
+/// The status of a [`Task`](crate::tasks::Task).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TaskStatus {
+    /// The task has been marked as completed.
+    Completed,
+    /// The task isn't completed and is due today or later.
+    Open,
+    /// The task is taken from the planner, isn't completed and was due before
+    /// today.
+    Overdue,
+    /// The task is taken from the classic agenda and was due before today.
+    /// Tasks in the classic agenda can't be marked as completed, so they're
+    /// never overdue.
+    Past,
+}
+
+impl TaskStatus {
+    fn new(is_completed: bool, is_planned: bool, due: NaiveDate, today: NaiveDate) -> Self {
+        if is_completed {
+            TaskStatus::Completed
+        } else if due >= today {
+            TaskStatus::Open
+        } else if is_planned {
+            TaskStatus::Overdue
+        } else {
+            TaskStatus::Past
+        }
+    }
+}
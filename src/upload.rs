@@ -1,6 +1,10 @@
 //! File uploads for use around the platform.
 
-use crate::{error::Result, http::TrySend, Client};
+use crate::{
+    error::Result,
+    http::{TryJson, TrySend},
+    Client,
+};
 use bytes::Bytes;
 use futures::{TryStream, TryStreamExt};
 use reqwest::{
@@ -21,7 +25,7 @@ pub async fn get_upload_directory(client: &Client<'_>) -> Result<UploadDirectory
         .get(&url)
         .try_send()
         .await?
-        .json()
+        .try_json()
         .await?;
     Ok(response.upload_dir)
 }